@@ -0,0 +1,362 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT text editor and compiler
+    limitation.rs
+    Nicholas Soucier
+
+    Headless command line runner, builds and executes a Limitation program against stdin and stdout
+    without opening the editor window.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use limitation_core::lexer::token_name;
+use limitation_core::{BufferInput, BufferOutput, InterpreterObject, ReaderInput, VmConfig, Watchpoint, WriterOutput};
+use std::env;
+use std::fs;
+use std::process;
+
+//Exit codes reported back to the shell
+const EXIT_BUILD_FAILURE: i32 = 1;
+const EXIT_RUNTIME_FAILURE: i32 = 2;
+const EXIT_USAGE: i32 = 64;
+const EXIT_IO_FAILURE: i32 = 74;
+
+//Loops and lines listed in the --profile report
+const PROFILE_REPORT_LENGTH: usize = 5;
+
+const USAGE: &str = "Usage: limitation run [options] <file>
+       limitation coverage <file> [input file...]
+
+Commands:
+    run <file>                       Build and execute a Limitation program, reading input from stdin
+    coverage <file> [input file...]  Execute a program once per input file, one input per line, and
+                                     report which tokens and '{ }' branches never ran
+    help                             Show this message
+
+Options for run:
+    --watch-cell <cell>            Report every write to an array cell
+    --watch-value <cell>=<value>   Report when an array cell is set to a value
+    --watch-stack <depth>          Report when the stack reaches a depth
+    --trace <file>                 Write every executed token to a JSON Lines file
+    --profile                      Report the hot loops and lines on stderr once the program stops
+    --config <file>                Read VM settings from a file of 'name = value' lines
+    --tape-length <cells>          Cells in the array (default 512, at most 16777216)
+    --max-cell <value>             Largest value a cell can hold (default 16777216)
+    --max-stack <depth>            Values the stack can hold (default 65536)
+    --max-output <bytes>           Bytes of output the program may write (default 1048576)
+    --input-range <policy>         What to do with a '?0' number above the largest cell value:
+                                   reprompt (default), clamp, or fault
+    --string-input <mode>          How '?a' stores text: zero (zero-terminated, default), length
+                                   (length-prefixed), or raw
+    --cell-overflow <policy>       What '+' and '-' do past the range of a cell: trap (default), wrap,
+                                   or saturate
+    --pointer-overflow <policy>    What '<', '>' and '^' do past either end of the array: trap
+                                   (default), wrap, or saturate
+    --history-limit <steps>        Steps kept for stepping backwards (default 10000, 0 keeps none)
+
+Exit codes: 0 success, 1 build failure, 2 runtime failure";
+
+//Everything given on the command line for a run
+struct RunOptions {
+    path: String,
+    watchpoints: Vec<Watchpoint>,
+    trace: Option<String>,
+    profile: bool,
+    config: VmConfig,
+}
+
+fn main(){
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = args.first().map(|arg| arg.as_str());
+    match command {
+        Some("run") => {
+            let options = parse_run_options(&args[1..]);
+            process::exit(run_file(&options));
+        }
+        Some("coverage") => {
+            process::exit(run_coverage(&args[1..]));
+        }
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+        }
+        Some(other) => {
+            usage_error(&format!("unknown command '{}'", other));
+        }
+        None => {
+            usage_error("no command given");
+        }
+    }
+}
+
+//Print the usage message and leave with the usage exit code
+fn usage_error(message: &str) -> ! {
+    eprintln!("limitation: {}\n\n{}", message, USAGE);
+    process::exit(EXIT_USAGE);
+}
+
+//Read the options and the program file for the run command
+fn parse_run_options(args: &[String]) -> RunOptions {
+    let mut path: Option<String> = None;
+    let mut watchpoints = Vec::new();
+    let mut trace = None;
+    let mut profile = false;
+    let mut config_path: Option<String> = None;
+    //Settings given as options, applied over the settings file
+    let mut settings: Vec<(&str, &String)> = Vec::new();
+    let mut remaining = args.iter();
+    while let Some(arg) = remaining.next() {
+        match arg.as_str() {
+            "--watch-cell" => {
+                let cell = parse_number(arg, remaining.next());
+                watchpoints.push(Watchpoint::CellWrite(cell));
+            }
+            "--watch-value" => {
+                let value = option_value(arg, remaining.next());
+                let (cell, target) = match value.split_once('=') {
+                    Some((cell, target)) => (cell.parse::<usize>().ok(), target.parse::<u32>().ok()),
+                    None => (None, None),
+                };
+                match (cell, target) {
+                    (Some(cell), Some(target)) => watchpoints.push(Watchpoint::CellValue(cell, target)),
+                    _ => usage_error(&format!("{} expects <cell>=<value>, got '{}'", arg, value)),
+                }
+            }
+            "--watch-stack" => {
+                let depth = parse_number(arg, remaining.next());
+                watchpoints.push(Watchpoint::StackDepth(depth));
+            }
+            "--trace" => {
+                trace = Some(option_value(arg, remaining.next()).clone());
+            }
+            "--profile" => {
+                profile = true;
+            }
+            "--config" => {
+                config_path = Some(option_value(arg, remaining.next()).clone());
+            }
+            "--tape-length" => settings.push(("tape_length", option_value(arg, remaining.next()))),
+            "--max-cell" => settings.push(("max_cell_value", option_value(arg, remaining.next()))),
+            "--max-stack" => settings.push(("max_stack_depth", option_value(arg, remaining.next()))),
+            "--max-output" => settings.push(("max_output_size", option_value(arg, remaining.next()))),
+            "--input-range" => settings.push(("input_range", option_value(arg, remaining.next()))),
+            "--string-input" => settings.push(("string_input", option_value(arg, remaining.next()))),
+            "--cell-overflow" => settings.push(("cell_overflow", option_value(arg, remaining.next()))),
+            "--pointer-overflow" => settings.push(("pointer_overflow", option_value(arg, remaining.next()))),
+            "--history-limit" => settings.push(("history_limit", option_value(arg, remaining.next()))),
+            option if option.starts_with("--") => {
+                usage_error(&format!("unknown option '{}'", option));
+            }
+            file => {
+                if path.is_some() {
+                    usage_error("run expects exactly one program file");
+                }
+                path = Some(file.to_string());
+            }
+        }
+    }
+    let mut config = match config_path {
+        Some(config_path) => read_config(&config_path),
+        None => VmConfig::default(),
+    };
+    for (name, value) in settings {
+        if let Err(message) = config.set(name, value) {
+            usage_error(&message);
+        }
+    }
+    match path {
+        Some(path) => return RunOptions { path, watchpoints, trace, profile, config },
+        None => usage_error("run expects exactly one program file"),
+    }
+}
+
+//Read a VM settings file, leaving if it cannot be read or used
+fn read_config(path: &String) -> VmConfig {
+    let text = match read_source(path) {
+        Some(text) => text,
+        None => process::exit(EXIT_IO_FAILURE),
+    };
+    match VmConfig::parse(&text) {
+        Ok(config) => return config,
+        Err(message) => usage_error(&format!("{}: {}", path, message)),
+    }
+}
+
+//The value following an option, which must be present
+fn option_value<'a>(option: &str, value: Option<&'a String>) -> &'a String {
+    match value {
+        Some(value) => return value,
+        None => usage_error(&format!("{} expects a value", option)),
+    }
+}
+
+//The value following an option, as a whole number
+fn parse_number(option: &str, value: Option<&String>) -> usize {
+    let value = option_value(option, value);
+    match value.parse::<usize>() {
+        Ok(number) => return number,
+        Err(_) => usage_error(&format!("{} expects a number, got '{}'", option, value)),
+    }
+}
+
+//Read a program file, reporting why it could not be read
+fn read_source(path: &String) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(source) => return Some(source),
+        Err(error) => {
+            eprintln!("limitation: unable to read {}: {}", path, error);
+            return None;
+        }
+    }
+}
+
+//Build the program, printing its diagnostics. Returns false if it cannot be executed
+fn build_program(language: &mut InterpreterObject, path: &String) -> bool {
+    let diagnostics = language.build();
+    for diagnostic in &diagnostics {
+        eprintln!("{}:{}:{}: {}[{}]: {}", path, diagnostic.span.line, diagnostic.span.column,
+            diagnostic.severity.label().to_lowercase(), diagnostic.code, diagnostic.message);
+    }
+    return language.is_executable();
+}
+
+//Execute a program once for every input file and print its combined coverage,
+//the annotated source on stdout, then the summary
+fn run_coverage(args: &[String]) -> i32 {
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => usage_error("coverage expects a program file"),
+    };
+    let source = match read_source(path) {
+        Some(source) => source,
+        None => return EXIT_IO_FAILURE,
+    };
+    let mut inputs: Vec<Option<&String>> = args[1..].iter().map(Some).collect();
+    if inputs.is_empty() {
+        inputs.push(None);
+    }
+
+    let mut language = InterpreterObject::new();
+    language.set_source(&source);
+    language.set_history_limit(0);
+    language.set_output_sink(Box::new(BufferOutput::new()));
+    let mut coverage = None;
+    let mut exit_code = 0;
+    for input in inputs {
+        let text = match input {
+            Some(input) => match read_source(input) {
+                Some(text) => text,
+                None => return EXIT_IO_FAILURE,
+            },
+            None => String::new(),
+        };
+        if !build_program(&mut language, path) {
+            return EXIT_BUILD_FAILURE;
+        }
+        let name = input.map(|input| input.as_str()).unwrap_or("(no input)");
+        language.set_input_source(Box::new(BufferInput::new(text.as_bytes())));
+        if let Err(reason) = execute_to_end(&mut language) {
+            eprintln!("limitation: {} did not run to the end: {}", name, reason);
+            exit_code = EXIT_RUNTIME_FAILURE;
+        }
+        match coverage.as_mut() {
+            None => coverage = Some(language.get_coverage()),
+            Some(total) => {
+                total.merge(&language.get_coverage());
+            }
+        }
+    }
+
+    if let Some(coverage) = coverage {
+        print!("{}\n{}", coverage.annotate(&source), coverage.summary());
+    }
+    return exit_code;
+}
+
+//Execute a built program to the end, its input tokens answered by its input source.
+//Gives the reason if it stopped early, on an error or because it ran out of input
+fn execute_to_end(language: &mut InterpreterObject) -> Result<(), String> {
+    language.start_execution();
+    while !language.is_finished() {
+        if language.is_waiting() {
+            return Err("ran out of input".to_string());
+        } else if language.can_step() {
+            language.execute_step().map_err(|error| error.to_string())?;
+        } else if language.is_paused() {
+            language.resume_execution();
+        } else {
+            return Err("the program could not be executed".to_string());
+        }
+    }
+    return Ok(());
+}
+
+//Build and execute a program file, returning the exit code for the process
+fn run_file(options: &RunOptions) -> i32 {
+    let path = &options.path;
+    let source = match read_source(path) {
+        Some(source) => source,
+        None => return EXIT_IO_FAILURE,
+    };
+
+    let mut language = InterpreterObject::new();
+    language.set_source(&source);
+    for watchpoint in &options.watchpoints {
+        language.add_watchpoint(*watchpoint);
+    }
+    language.set_config(options.config);
+    if !build_program(&mut language, path) {
+        return EXIT_BUILD_FAILURE;
+    }
+    if let Some(trace) = &options.trace {
+        if let Err(error) = language.start_trace_file(trace) {
+            eprintln!("limitation: unable to create {}: {}", trace, error);
+            return EXIT_IO_FAILURE;
+        }
+    }
+
+    //Program output goes straight to stdout, the interpreter's own messages go to stderr
+    language.set_input_source(Box::new(ReaderInput::stdin()));
+    language.set_output_sink(Box::new(WriterOutput::stdout()));
+    let mut printed = language.get_output().len();
+    language.start_execution();
+    while !language.is_finished() && language.can_step() {
+        let result = language.execute_step();
+        let messages = &language.get_output()[printed..];
+        if !messages.is_empty() {
+            eprint!("{}", messages.trim_start_matches('\n'));
+            if !messages.ends_with('\n') {
+                eprintln!();
+            }
+        }
+        printed = language.get_output().len();
+        if let Err(error) = result {
+            let token = token_name(language.get_tokens()[error.token].kind);
+            eprintln!("limitation: stopped on {} with the pointer on cell {} holding {}", token, error.pointer, error.value);
+            break;
+        }
+
+        //Still waiting after the step means stdin has nothing left to read. The program has not
+        //finished, so the trace and profile are still written and the exit code is a runtime failure
+        if language.is_waiting() {
+            match language.current_span() {
+                Some(span) => eprintln!("limitation: reached the end of stdin while waiting for input at line {}, column {}", span.line, span.column),
+                None => eprintln!("limitation: reached the end of stdin while waiting for input"),
+            }
+            break;
+        }
+        //Watchpoints only report on the command line, execution carries on
+        if language.is_paused() {
+            language.resume_execution();
+            printed = language.get_output().len();
+        }
+    }
+    language.stop_trace();
+    if options.profile {
+        eprint!("\n{}", language.get_profile().report(PROFILE_REPORT_LENGTH));
+    }
+
+    if language.is_finished() {
+        return 0;
+    }
+    return EXIT_RUNTIME_FAILURE;
+}
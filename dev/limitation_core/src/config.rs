@@ -0,0 +1,274 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    config.rs
+    Nicholas Soucier
+
+    VM settings, the limits of the execution environment, what happens at them, and how input is read into it.
+    Can be read from a settings file of 'name = value' lines, with '//' comments.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::io::{InputRangePolicy, StringInputMode};
+use std::fmt;
+
+//Defaults, the limits the language has always had
+pub const DEFAULT_TAPE_LENGTH: usize = 512;
+pub const DEFAULT_MAX_CELL_VALUE: u32 = 16777216;
+pub const DEFAULT_MAX_STACK_DEPTH: usize = 65536;
+pub const DEFAULT_MAX_OUTPUT_SIZE: usize = 1048576;
+pub const DEFAULT_HISTORY_LIMIT: usize = 10000;
+//Largest array a build will allocate
+pub const MAX_TAPE_LENGTH: usize = 16777216;
+
+//What happens when a value goes past the end of its range
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    //Halt the program with a runtime error
+    Trap,
+    //Carry on from the other end of the range
+    Wrap,
+    //Stay at the end of the range
+    Saturate,
+}
+
+impl OverflowPolicy {
+    //Bring a value back into 0..=max as the policy says, None if it traps
+    pub fn apply(&self, value: i64, max: i64) -> Option<i64> {
+        if value >= 0 && value <= max {
+            return Some(value);
+        }
+        match self {
+            OverflowPolicy::Trap => return None,
+            OverflowPolicy::Wrap => return Some(value.rem_euclid(max + 1)),
+            OverflowPolicy::Saturate => return Some(value.clamp(0, max)),
+        }
+    }
+
+    fn parse(name: &str, value: &str) -> Result<OverflowPolicy, String> {
+        match value {
+            "trap" => return Ok(OverflowPolicy::Trap),
+            "wrap" => return Ok(OverflowPolicy::Wrap),
+            "saturate" => return Ok(OverflowPolicy::Saturate),
+            _ => return Err(format!("{} expects trap, wrap or saturate, got '{}'", name, value)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            OverflowPolicy::Trap => return "trap",
+            OverflowPolicy::Wrap => return "wrap",
+            OverflowPolicy::Saturate => return "saturate",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VmConfig {
+    //Cells in the array
+    pub tape_length: usize,
+    //Largest value a cell can hold
+    pub max_cell_value: u32,
+    //Values the stack can hold
+    pub max_stack_depth: usize,
+    //Bytes of output the program may write
+    pub max_output_size: usize,
+    //What to do with a '?0' number above max_cell_value
+    pub input_range: InputRangePolicy,
+    //How '?a' lays text out in the array
+    pub string_input: StringInputMode,
+    //'+' above max_cell_value and '-' below 0
+    pub cell_overflow: OverflowPolicy,
+    //'<', '>' and '^' moving the pointer off either end of the array
+    pub pointer_overflow: OverflowPolicy,
+    //Steps kept for stepping backwards, 0 turns the history off
+    pub history_limit: usize,
+}
+
+impl Default for VmConfig {
+    fn default() -> VmConfig {
+        VmConfig {
+            tape_length: DEFAULT_TAPE_LENGTH,
+            max_cell_value: DEFAULT_MAX_CELL_VALUE,
+            max_stack_depth: DEFAULT_MAX_STACK_DEPTH,
+            max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+            input_range: InputRangePolicy::Reprompt,
+            string_input: StringInputMode::ZeroTerminated,
+            cell_overflow: OverflowPolicy::Trap,
+            pointer_overflow: OverflowPolicy::Trap,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+        }
+    }
+}
+
+impl VmConfig {
+    pub fn new() -> VmConfig {
+        return VmConfig::default();
+    }
+
+    //Read settings from text, e.g. "tape_length = 1024". Settings left out keep their defaults
+    pub fn parse(text: &str) -> Result<VmConfig, String> {
+        let mut config = VmConfig::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = match line.find("//") {
+                Some(comment) => &line[..comment],
+                None => line,
+            }.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = match line.split_once('=') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => return Err(format!("line {}: expected 'name = value', got '{}'", number + 1, line)),
+            };
+            if let Err(message) = config.set(name, value) {
+                return Err(format!("line {}: {}", number + 1, message));
+            }
+        }
+        return Ok(config);
+    }
+
+    //Change one setting by name, the names used in a settings file
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "tape_length" => self.tape_length = parse_limit_up_to(name, value, MAX_TAPE_LENGTH)?,
+            "max_cell_value" => self.max_cell_value = parse_limit(name, value)?,
+            "max_stack_depth" => self.max_stack_depth = parse_limit(name, value)?,
+            "max_output_size" => self.max_output_size = parse_limit(name, value)?,
+            "input_range" => {
+                self.input_range = match value {
+                    "reprompt" => InputRangePolicy::Reprompt,
+                    "clamp" => InputRangePolicy::Clamp,
+                    "fault" => InputRangePolicy::Fault,
+                    _ => return Err(format!("input_range expects reprompt, clamp or fault, got '{}'", value)),
+                }
+            }
+            "string_input" => {
+                self.string_input = match value {
+                    "zero" => StringInputMode::ZeroTerminated,
+                    "length" => StringInputMode::LengthPrefixed,
+                    "raw" => StringInputMode::Raw,
+                    _ => return Err(format!("string_input expects zero, length or raw, got '{}'", value)),
+                }
+            }
+            "cell_overflow" => self.cell_overflow = OverflowPolicy::parse(name, value)?,
+            "pointer_overflow" => self.pointer_overflow = OverflowPolicy::parse(name, value)?,
+            "history_limit" => {
+                self.history_limit = match value.parse::<usize>() {
+                    Ok(limit) => limit,
+                    Err(_) => return Err(format!("history_limit expects a whole number, got '{}'", value)),
+                }
+            }
+            _ => return Err(format!("unknown setting '{}'", name)),
+        }
+        return Ok(());
+    }
+}
+
+//Settings written back out in the form parse reads
+impl fmt::Display for VmConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let input_range = match self.input_range {
+            InputRangePolicy::Reprompt => "reprompt",
+            InputRangePolicy::Clamp => "clamp",
+            InputRangePolicy::Fault => "fault",
+        };
+        let string_input = match self.string_input {
+            StringInputMode::ZeroTerminated => "zero",
+            StringInputMode::LengthPrefixed => "length",
+            StringInputMode::Raw => "raw",
+        };
+        writeln!(f, "tape_length = {}", self.tape_length)?;
+        writeln!(f, "max_cell_value = {}", self.max_cell_value)?;
+        writeln!(f, "max_stack_depth = {}", self.max_stack_depth)?;
+        writeln!(f, "max_output_size = {}", self.max_output_size)?;
+        writeln!(f, "input_range = {}", input_range)?;
+        writeln!(f, "string_input = {}", string_input)?;
+        writeln!(f, "cell_overflow = {}", self.cell_overflow.name())?;
+        writeln!(f, "pointer_overflow = {}", self.pointer_overflow.name())?;
+        writeln!(f, "history_limit = {}", self.history_limit)
+    }
+}
+
+//A limit that is allocated up front has to stay below a fixed size as well
+fn parse_limit_up_to(name: &str, value: &str, max: usize) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(limit) if limit >= 1 && limit <= max => return Ok(limit),
+        _ => return Err(format!("{} expects a whole number from 1 to {}, got '{}'", name, max, value)),
+    }
+}
+
+//A limit has to be a whole number above 0
+fn parse_limit<T: std::str::FromStr + PartialEq + Default>(name: &str, value: &str) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(limit) if limit != T::default() => return Ok(limit),
+        _ => return Err(format!("{} expects a whole number above 0, got '{}'", name, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_in_range_are_left_alone() {
+        for policy in [OverflowPolicy::Trap, OverflowPolicy::Wrap, OverflowPolicy::Saturate] {
+            assert_eq!(policy.apply(0, 9), Some(0));
+            assert_eq!(policy.apply(5, 9), Some(5));
+            assert_eq!(policy.apply(9, 9), Some(9));
+        }
+    }
+
+    #[test]
+    fn trap_refuses_values_past_either_end() {
+        assert_eq!(OverflowPolicy::Trap.apply(10, 9), None);
+        assert_eq!(OverflowPolicy::Trap.apply(-1, 9), None);
+    }
+
+    #[test]
+    fn wrap_carries_on_from_the_other_end() {
+        assert_eq!(OverflowPolicy::Wrap.apply(10, 9), Some(0));
+        assert_eq!(OverflowPolicy::Wrap.apply(-1, 9), Some(9));
+        assert_eq!(OverflowPolicy::Wrap.apply(25, 9), Some(5));
+        assert_eq!(OverflowPolicy::Wrap.apply(-11, 9), Some(9));
+        //A cell at the default largest value
+        let max = DEFAULT_MAX_CELL_VALUE as i64;
+        assert_eq!(OverflowPolicy::Wrap.apply(max + 1, max), Some(0));
+    }
+
+    #[test]
+    fn saturate_stays_at_the_end() {
+        assert_eq!(OverflowPolicy::Saturate.apply(10, 9), Some(9));
+        assert_eq!(OverflowPolicy::Saturate.apply(-1, 9), Some(0));
+        assert_eq!(OverflowPolicy::Saturate.apply(i64::MAX, 9), Some(9));
+    }
+
+    #[test]
+    fn policies_are_read_by_name() {
+        let config = VmConfig::parse("cell_overflow = wrap\npointer_overflow = saturate //edges").unwrap();
+        assert_eq!(config.cell_overflow, OverflowPolicy::Wrap);
+        assert_eq!(config.pointer_overflow, OverflowPolicy::Saturate);
+        assert_eq!(VmConfig::parse(&config.to_string()).unwrap(), config);
+        assert_eq!(VmConfig::parse("cell_overflow = clamp").unwrap_err(), "line 1: cell_overflow expects trap, wrap or saturate, got 'clamp'");
+    }
+
+    #[test]
+    fn tape_length_is_bounded() {
+        assert_eq!(VmConfig::parse("tape_length = 1").unwrap().tape_length, 1);
+        assert_eq!(VmConfig::parse(&format!("tape_length = {}", MAX_TAPE_LENGTH)).unwrap().tape_length, MAX_TAPE_LENGTH);
+        let expected = format!("line 1: tape_length expects a whole number from 1 to {}, got '", MAX_TAPE_LENGTH);
+        for value in ["0", "16777217", "99999999999999999", "-1", "many"] {
+            assert_eq!(VmConfig::parse(&format!("tape_length = {}", value)).unwrap_err(), format!("{}{}'", expected, value));
+        }
+    }
+
+    #[test]
+    fn history_limit_can_turn_the_history_off() {
+        assert_eq!(VmConfig::default().history_limit, DEFAULT_HISTORY_LIMIT);
+        assert_eq!(VmConfig::parse("history_limit = 0").unwrap().history_limit, 0);
+        let config = VmConfig::parse("history_limit = 250").unwrap();
+        assert_eq!(config.history_limit, 250);
+        assert_eq!(VmConfig::parse(&config.to_string()).unwrap(), config);
+        assert_eq!(VmConfig::parse("history_limit = -1").unwrap_err(), "line 1: history_limit expects a whole number, got '-1'");
+    }
+}
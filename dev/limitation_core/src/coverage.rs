@@ -0,0 +1,190 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    coverage.rs
+    Nicholas Soucier
+
+    Token coverage, which tokens and which '{ }' branches ran over one or more executions of a program.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::lexer::*;
+use crate::profile::percent;
+use std::fmt::Write;
+
+pub struct Coverage {
+    tokens: Vec<Token>,
+    token_counts: Vec<usize>,
+    //Times each '{' jumped past its '}', the rest of its executions entered the body
+    skip_counts: Vec<usize>,
+    runs: usize,
+}
+
+impl Coverage {
+    pub fn new(tokens: &[Token], token_counts: &[usize], skip_counts: &[usize]) -> Coverage {
+        Coverage {
+            tokens: tokens.to_vec(),
+            token_counts: token_counts.to_vec(),
+            skip_counts: skip_counts.to_vec(),
+            runs: 1,
+        }
+    }
+
+    //Add the coverage of another run of the same program.
+    //Returns false, leaving this coverage as it was, if the other run was built from different tokens
+    pub fn merge(&mut self, other: &Coverage) -> bool {
+        let same_program = self.tokens.len() == other.tokens.len()
+            && self.tokens.iter().zip(other.tokens.iter()).all(|(a, b)| a.kind == b.kind && a.span == b.span);
+        if !same_program {
+            return false;
+        }
+        for token in 0..self.tokens.len() {
+            self.token_counts[token] += other.token_counts[token];
+            self.skip_counts[token] += other.skip_counts[token];
+        }
+        self.runs += other.runs;
+        return true;
+    }
+
+    pub fn get_runs(&self) -> usize {
+        return self.runs;
+    }
+    //True if the token ran in any of the runs
+    pub fn is_covered(&self, token: usize) -> bool {
+        return self.token_counts.get(token).copied().unwrap_or(0) > 0;
+    }
+    //Times a '{' jumped past its body, and times it entered it
+    pub fn get_branch_counts(&self, token: usize) -> (usize, usize) {
+        let skipped = self.skip_counts[token];
+        return (skipped, self.token_counts[token] - skipped);
+    }
+    //Number of tokens in the program, and how many of them ran
+    pub fn get_token_totals(&self) -> (usize, usize) {
+        let total = self.program_tokens().count();
+        let covered = self.program_tokens().filter(|token| self.token_counts[*token] > 0).count();
+        return (total, covered);
+    }
+    //Number of '{ }' branch outcomes, two for every '{', and how many of them happened
+    pub fn get_branch_totals(&self) -> (usize, usize) {
+        let mut total = 0;
+        let mut covered = 0;
+        for token in self.branch_tokens() {
+            let (skipped, entered) = self.get_branch_counts(token);
+            total += 2;
+            covered += (skipped > 0) as usize + (entered > 0) as usize;
+        }
+        return (total, covered);
+    }
+
+    //Plain text summary of the totals, followed by every token and branch outcome that never happened
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        let (tokens, covered_tokens) = self.get_token_totals();
+        let (branches, covered_branches) = self.get_branch_totals();
+        writeln!(summary, "Coverage over {} run(s):", self.runs).unwrap_or_default();
+        writeln!(summary, "    tokens:   {} of {} executed ({:.1}%)", covered_tokens, tokens, percent(covered_tokens, tokens)).unwrap_or_default();
+        writeln!(summary, "    branches: {} of {} taken ({:.1}%)", covered_branches, branches, percent(covered_branches, branches)).unwrap_or_default();
+
+        let missed: Vec<usize> = self.program_tokens().filter(|token| self.token_counts[*token] == 0).collect();
+        if !missed.is_empty() {
+            writeln!(summary, "Never executed:").unwrap_or_default();
+            for token in missed {
+                let span = self.tokens[token].span;
+                writeln!(summary, "    line {}, column {}: {}", span.line, span.column, token_name(self.tokens[token].kind)).unwrap_or_default();
+            }
+        }
+        let partial: Vec<String> = self.branch_tokens().filter_map(|token| self.branch_note(token)).collect();
+        if !partial.is_empty() {
+            writeln!(summary, "Untested branches:").unwrap_or_default();
+            for note in partial {
+                writeln!(summary, "    {}", note).unwrap_or_default();
+            }
+        }
+        return summary;
+    }
+
+    //The source with every line annotated by its execution count, in the style of gcov:
+    //'-' for a line with no tokens, '#####' for a line whose tokens never ran, and a '*' after the
+    //count of a line that only partly ran. Untested branches are listed under their line
+    pub fn annotate(&self, source: &str) -> String {
+        let mut annotated = String::new();
+        for (line, text) in source.lines().enumerate() {
+            let line_tokens: Vec<usize> = self.program_tokens().filter(|token| self.tokens[*token].span.line == line + 1).collect();
+            let hits = line_tokens.iter().map(|token| self.token_counts[*token]).max();
+            let partial = line_tokens.iter().any(|token| self.token_counts[*token] == 0);
+            match hits {
+                None => writeln!(annotated, "{:>9} | {}", "-", text),
+                Some(0) => writeln!(annotated, "{:>9} | {}", "#####", text),
+                Some(hits) if partial => writeln!(annotated, "{:>8}* | {}", hits, text),
+                Some(hits) => writeln!(annotated, "{:>9} | {}", hits, text),
+            }.unwrap_or_default();
+            for token in line_tokens.iter().filter(|token| self.tokens[**token].kind == CONDITIONALJUMP) {
+                if let Some(note) = self.branch_note(*token) {
+                    writeln!(annotated, "{:>9} | {}", "", note).unwrap_or_default();
+                }
+            }
+        }
+        return annotated;
+    }
+
+    //What a '{' never did, None if both its outcomes happened
+    fn branch_note(&self, token: usize) -> Option<String> {
+        let span = self.tokens[token].span;
+        let outcome = match self.get_branch_counts(token) {
+            (0, 0) => "never reached",
+            (0, _) => "never skipped its body",
+            (_, 0) => "never entered its body",
+            _ => return None,
+        };
+        return Some(format!("'{{' at line {}, column {} {}", span.line, span.column, outcome));
+    }
+
+    //Every token of the program, leaving out the end of input. A '}' is only a marker that jumps
+    //land after, so it is covered by the branch of its '{' instead
+    fn program_tokens(&self) -> impl Iterator<Item = usize> + '_ {
+        return (0..self.tokens.len()).filter(|token| self.tokens[*token].kind != ENDOFINPUT && self.tokens[*token].kind != CONDITIONALMARKER);
+    }
+    fn branch_tokens(&self) -> impl Iterator<Item = usize> + '_ {
+        return (0..self.tokens.len()).filter(|token| self.tokens[*token].kind == CONDITIONALJUMP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lang::InterpreterObject;
+
+    fn run_to_input(source: &str) -> InterpreterObject {
+        let mut language = InterpreterObject::new();
+        language.set_source(source);
+        language.build();
+        language.start_execution();
+        language.run().unwrap();
+        return language;
+    }
+
+    #[test]
+    fn waiting_input_token_is_not_covered() {
+        let mut language = run_to_input("+?0\n{+}");
+        language.pause_execution();
+        let coverage = language.get_coverage();
+        assert!(coverage.is_covered(0));
+        assert!(!coverage.is_covered(1));
+        assert_eq!(coverage.get_token_totals(), (4, 1));
+        assert!(coverage.annotate(language.get_source()).starts_with("       1* | +?0\n"));
+
+        language.resume_execution();
+        assert!(language.submit_input("0", false));
+        language.run().unwrap();
+        let mut coverage = language.get_coverage();
+        assert_eq!(coverage.get_token_totals(), (4, 3));
+        assert_eq!(coverage.get_branch_counts(2), (1, 0));
+
+        let mut second = run_to_input("+?0\n{+}");
+        assert!(second.submit_input("1", false));
+        second.run().unwrap();
+        assert!(coverage.merge(&second.get_coverage()));
+        assert_eq!(coverage.get_token_totals(), (4, 4));
+        assert_eq!(coverage.get_branch_totals(), (2, 2));
+        assert_eq!(coverage.get_branch_counts(1), (0, 2));
+    }
+}
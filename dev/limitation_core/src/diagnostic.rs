@@ -0,0 +1,91 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    diagnostic.rs
+    Nicholas Soucier
+
+    Build diagnostics, each problem found while building carries a severity, a short code
+    and the place in the source where it happened.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use std::fmt;
+
+//Diagnostic codes
+pub const UNRECOGNIZED_CHARACTER: &str = "E001";   //Character that does not start any token
+pub const INVALID_INPUT_TYPE: &str = "E002";       //'?' not followed by '0' or 'a'
+pub const INVALID_OUTPUT_TYPE: &str = "E003";      //'&' not followed by '0' or 'a'
+pub const UNMATCHED_CONDITIONAL: &str = "E004";    //'{' or '}' without a partner
+pub const UNMATCHED_LOOP: &str = "E005";           //'=' or ':' without a partner
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    //Tag used when printing to the console
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Error => return "ERROR",
+            Severity::Warning => return "WARNING",
+        }
+    }
+}
+
+//A region of the source. 'start' and 'end' are byte offsets into the source text,
+//lines and columns both start at 1 and the end offset and column are exclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize, end_line: usize, end_column: usize) -> Span {
+        Span {
+            start,
+            end,
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: String, span: Span) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message,
+            span,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        return self.severity == Severity::Error;
+    }
+}
+
+//Console format, e.g. "[ERROR]: E001 at line 3, column 5: Unable to recognize character as a token: x"
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]: {} at line {}, column {}: {}", self.severity.label(), self.code,
+            self.span.line, self.span.column, self.message)
+    }
+}
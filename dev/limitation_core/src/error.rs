@@ -0,0 +1,92 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    error.rs
+    Nicholas Soucier
+
+    Runtime errors, each failure that halts a running program carries what went wrong, the token
+    it happened on, and the pointer and pointed value at the time.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::diagnostic::Span;
+use std::fmt;
+
+//What went wrong
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuntimeErrorKind {
+    //'+' on a cell already holding the largest value
+    CellAboveMax { max: u32 },
+    //'-' on a cell holding 0
+    CellBelowZero,
+    //'<' on cell 0
+    PointerBelowZero,
+    //'>' on the last cell, or '^' to a cell past it
+    PointerPastEnd { target: u64, last: usize },
+    //'#' with the stack holding as many values as it can
+    StackFull { limit: usize },
+    //'$' with nothing on the stack
+    StackEmpty,
+    //'&0' or '&a' past the bytes the program may write
+    OutputLimit { limit: usize },
+    //'?0' given a number above the largest value, with the input range policy set to fault
+    InputAboveMax { input: String, max: u32 },
+    //'?a' given more text than fits in the array from the pointer
+    TextTooLong { length: usize },
+    //'?a' given text with a character code, or length, above the largest value, with the cell overflow policy set to trap
+    TextAboveMax { value: usize, max: u32 },
+    //'&a' on a value that is not a character
+    InvalidCharacter,
+    //A token the interpreter does not know how to execute
+    CorruptTokens,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    //Index of the token that failed in the token list, and where it is in the source
+    pub token: usize,
+    pub span: Span,
+    //The pointer and the pointed value when the token failed
+    pub pointer: usize,
+    pub value: u32,
+}
+
+impl RuntimeError {
+    pub fn new(kind: RuntimeErrorKind, token: usize, span: Span, pointer: usize, value: u32) -> RuntimeError {
+        RuntimeError {
+            kind,
+            token,
+            span,
+            pointer,
+            value,
+        }
+    }
+
+    //What went wrong, without its place in the source
+    pub fn message(&self) -> String {
+        match &self.kind {
+            RuntimeErrorKind::CellAboveMax { max } => return format!("Attempted to increment value at index {} above integer max {}.", self.pointer, max),
+            RuntimeErrorKind::CellBelowZero => return format!("Attempted to decrement value at index {} below 0.", self.pointer),
+            RuntimeErrorKind::PointerBelowZero => return "Attempted to shift array index below 0.".to_string(),
+            RuntimeErrorKind::PointerPastEnd { target, last } => return format!("Attempted to shift array index to {}, past the last cell, {}.", target, last),
+            RuntimeErrorKind::StackFull { limit } => return format!("Attempted to push to a full stack, it holds at most {} values.", limit),
+            RuntimeErrorKind::StackEmpty => return "Attempted to pop from empty stack.".to_string(),
+            RuntimeErrorKind::OutputLimit { limit } => return format!("Program output reached the limit of {} bytes.", limit),
+            RuntimeErrorKind::InputAboveMax { input, max } => return format!("Input {} is above the integer max {}.", input, max),
+            RuntimeErrorKind::TextTooLong { length } => return format!("Text input of {} character(s) does not fit in the array from index {}.", length, self.pointer),
+            RuntimeErrorKind::TextAboveMax { value, max } => return format!("Text input would store {} in the array from index {}, above the integer max {}.", value, self.pointer, max),
+            RuntimeErrorKind::InvalidCharacter => return format!("Attempted to output {} at index {} as a character, it is not a valid character.", self.value, self.pointer),
+            RuntimeErrorKind::CorruptTokens => return "No idea how, but the token list is corrupted. Very sorry".to_string(),
+        }
+    }
+}
+
+//Console format, e.g. "Attempted to pop from empty stack. (line 2, column 7)"
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (line {}, column {})", self.message(), self.span.line, self.span.column)
+    }
+}
+
+impl std::error::Error for RuntimeError {}
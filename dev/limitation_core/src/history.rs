@@ -0,0 +1,28 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    history.rs
+    Nicholas Soucier
+
+    Execution history, what each step changed so the interpreter can step backwards.
+    ----------------------------------------------------------------------------------------------
+*/
+
+//A cell as it was before a step wrote to it
+pub struct CellChange {
+    pub cell: usize,
+    pub value: u32,
+    pub written_at: usize,
+}
+
+//Everything needed to undo one executed token
+pub struct StepRecord {
+    pub index: usize,
+    pub pointer: usize,
+    pub previous_index: Option<usize>,
+    pub cells: Vec<CellChange>,
+    pub stack_len: usize,
+    pub stack_popped: Option<u32>,
+    pub output_len: usize,
+    pub program_output_len: usize,
+}
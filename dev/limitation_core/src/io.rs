@@ -0,0 +1,275 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    io.rs
+    Nicholas Soucier
+
+    Where a program reads its '?0' and '?a' input from, where its '&0' and '&a' output goes,
+    and the channels that keep program output apart from echoed input and interpreter messages.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+//The kinds of text in the interpreter output, kept apart so they can be shown differently
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    //What the program printed with '&0' and '&a'
+    Program,
+    //Input lines echoed back as they were entered
+    Input,
+    //Build results, errors and other messages from the interpreter
+    Message,
+}
+
+//What an input token is waiting for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputKind {
+    //A whole number for '?0'
+    Number,
+    //A line of ASCII text for '?a'
+    Text,
+}
+
+//What to do with a number entered for '?0' that is above the largest value a cell can hold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputRangePolicy {
+    //Reject it and wait for another number
+    Reprompt,
+    //Store the largest value instead
+    Clamp,
+    //Halt the program with a runtime error
+    Fault,
+}
+
+//How '?a' lays a line of text out in the array, starting from the pointed cell.
+//The pointer is left on the cell just past the text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringInputMode {
+    //One character per cell, followed by a 0 cell that the pointer is left on
+    ZeroTerminated,
+    //The number of characters, then one character per cell
+    LengthPrefixed,
+    //One character per cell with nothing to mark the end
+    Raw,
+}
+
+//A source of input lines, one line answers one input token
+pub trait InputSource: Send {
+    //The next line without its line ending, None if there is nothing to read (yet)
+    fn read_line(&mut self) -> Option<String>;
+    //True if lines read should be echoed to the output, as if typed into it
+    fn echoes(&self) -> bool {
+        return false;
+    }
+}
+
+//Somewhere to write what the program outputs
+pub trait OutputSink: Send {
+    fn write_output(&mut self, text: &str);
+    //Called before the program waits for input, so prompts are seen
+    fn flush(&mut self) {}
+}
+
+//Input read from a byte buffer held in memory
+pub struct BufferInput {
+    lines: VecDeque<String>,
+}
+
+impl BufferInput {
+    pub fn new(bytes: &[u8]) -> BufferInput {
+        let text = String::from_utf8_lossy(bytes);
+        BufferInput {
+            lines: text.lines().map(|line| line.to_string()).collect(),
+        }
+    }
+}
+
+impl InputSource for BufferInput {
+    fn read_line(&mut self) -> Option<String> {
+        return self.lines.pop_front();
+    }
+}
+
+//Input read line by line from a file, stdin, or any other reader
+pub struct ReaderInput {
+    reader: Box<dyn BufRead + Send>,
+}
+
+impl ReaderInput {
+    pub fn new(reader: Box<dyn BufRead + Send>) -> ReaderInput {
+        ReaderInput { reader }
+    }
+    pub fn stdin() -> ReaderInput {
+        return ReaderInput::new(Box::new(BufReader::new(io::stdin())));
+    }
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<ReaderInput> {
+        let file = File::open(path)?;
+        return Ok(ReaderInput::new(Box::new(BufReader::new(file))));
+    }
+}
+
+impl InputSource for ReaderInput {
+    //A read error ends the input the same way the end of the file does
+    fn read_line(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => return Some(line.trim_end_matches(['\n', '\r']).to_string()),
+        }
+    }
+}
+
+//Lines typed into the editor console. The editor keeps a clone to submit lines while the
+//interpreter reads them, and they are echoed like anything else typed in the console
+#[derive(Clone, Default)]
+pub struct ConsoleInput {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl ConsoleInput {
+    pub fn new() -> ConsoleInput {
+        return ConsoleInput::default();
+    }
+    pub fn submit(&self, line: &str) {
+        self.lines.lock().unwrap().push_back(line.to_string());
+    }
+    pub fn clear(&self) {
+        self.lines.lock().unwrap().clear();
+    }
+}
+
+impl InputSource for ConsoleInput {
+    fn read_line(&mut self) -> Option<String> {
+        return self.lines.lock().unwrap().pop_front();
+    }
+    fn echoes(&self) -> bool {
+        return true;
+    }
+}
+
+//Output collected in memory. Clones share the same buffer, so it can be read back after running
+#[derive(Clone, Default)]
+pub struct BufferOutput {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl BufferOutput {
+    pub fn new() -> BufferOutput {
+        return BufferOutput::default();
+    }
+    pub fn get_bytes(&self) -> Vec<u8> {
+        return self.buffer.lock().unwrap().clone();
+    }
+    pub fn get_text(&self) -> String {
+        return String::from_utf8_lossy(&self.buffer.lock().unwrap()).to_string();
+    }
+}
+
+impl OutputSink for BufferOutput {
+    fn write_output(&mut self, text: &str) {
+        self.buffer.lock().unwrap().extend_from_slice(text.as_bytes());
+    }
+}
+
+//Output written to a file, stdout, or any other writer. Write errors are dropped,
+//the program carries on as if its output was read
+pub struct WriterOutput {
+    writer: Box<dyn Write + Send>,
+}
+
+impl WriterOutput {
+    pub fn new(writer: Box<dyn Write + Send>) -> WriterOutput {
+        WriterOutput { writer }
+    }
+    pub fn stdout() -> WriterOutput {
+        return WriterOutput::new(Box::new(io::stdout()));
+    }
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<WriterOutput> {
+        let file = File::create(path)?;
+        return Ok(WriterOutput::new(Box::new(io::BufWriter::new(file))));
+    }
+}
+
+impl OutputSink for WriterOutput {
+    fn write_output(&mut self, text: &str) {
+        self.writer.write_all(text.as_bytes()).unwrap_or_default();
+    }
+    fn flush(&mut self) {
+        self.writer.flush().unwrap_or_default();
+    }
+}
+
+impl Drop for WriterOutput {
+    fn drop(&mut self) {
+        self.writer.flush().unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::InterpreterObject;
+    use std::io::Cursor;
+
+    #[test]
+    fn buffers_keep_program_output_apart_from_messages() {
+        let mut language = InterpreterObject::new();
+        language.set_source("?0&0>?a<<&a");
+        language.build();
+        language.set_input_source(Box::new(BufferInput::new(b"3\nhi\n")));
+        let output = BufferOutput::new();
+        language.set_output_sink(Box::new(output.clone()));
+        language.start_execution();
+        language.run().unwrap();
+        assert!(language.is_finished());
+        assert_eq!(output.get_text(), "3h");
+        assert_eq!(output.get_bytes(), b"3h".to_vec());
+        //Only the interpreter's own messages are left, a buffer does not echo what it answers
+        assert_eq!(language.get_channel_output(Channel::Program), "");
+        assert_eq!(language.get_channel_output(Channel::Input), "");
+        assert_eq!(language.get_output(), &language.get_channel_output(Channel::Message));
+        assert!(language.get_output().contains("Finished Execution"));
+    }
+
+    #[test]
+    fn reader_input_reads_lines_without_their_endings() {
+        let mut input = ReaderInput::new(Box::new(Cursor::new(b"12\r\nsome text\n\nlast".to_vec())));
+        assert_eq!(input.read_line(), Some("12".to_string()));
+        assert_eq!(input.read_line(), Some("some text".to_string()));
+        assert_eq!(input.read_line(), Some(String::new()));
+        assert_eq!(input.read_line(), Some("last".to_string()));
+        assert_eq!(input.read_line(), None);
+        assert_eq!(input.read_line(), None);
+        assert!(!input.echoes());
+    }
+
+    #[test]
+    fn writer_output_writes_through_to_its_writer() {
+        let buffer = BufferOutput::new();
+        let shared = buffer.clone();
+        let mut output = WriterOutput::new(Box::new(SharedWriter(shared)));
+        output.write_output("4");
+        output.write_output("2\n");
+        output.flush();
+        assert_eq!(buffer.get_text(), "42\n");
+    }
+
+    //Lets a WriterOutput write into a BufferOutput that the test still holds
+    struct SharedWriter(BufferOutput);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write_output(&String::from_utf8_lossy(buf));
+            return Ok(buf.len());
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            return Ok(());
+        }
+    }
+}
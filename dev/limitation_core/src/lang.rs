@@ -0,0 +1,1640 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    NovelText text editor and compiler
+    lang.rs
+    Nicholas Soucier
+
+    lang file, used to control the compiler and executor of the novelty language. 
+    Takes plain strings and numbers only, all keyboard handling is left to the editor.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::config::VmConfig;
+use crate::coverage::Coverage;
+use crate::diagnostic;
+use crate::diagnostic::{Diagnostic, Span};
+use crate::error::{RuntimeError, RuntimeErrorKind};
+use crate::history::{CellChange, StepRecord};
+use crate::io::{Channel, InputKind, InputRangePolicy, InputSource, OutputSink, StringInputMode};
+use crate::lexer::*;
+use crate::profile::Profile;
+use crate::state::VmState;
+use crate::trace::{TraceEntry, TraceWriter};
+use crate::watch::Watchpoint;
+use std::collections::{HashSet, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub struct InterpreterObject {
+    input: String,
+    output: String,
+    channels: Vec<(usize, Channel)>,
+    token_list: Vec<Token>,
+    jump_table: Vec<usize>,
+    index: usize,
+    execute_array: Vec<u32>,
+    execute_stack: Vec<u32>,
+    execute_index: usize,
+    cell_written_at: Vec<usize>,
+    step_count: usize,
+    token_counts: Vec<usize>,
+    skip_counts: Vec<usize>,
+    state: VmState,
+    //State to go back to once the waiting input token is answered
+    input_resume: VmState,
+    fault: Option<RuntimeError>,
+    history: VecDeque<StepRecord>,
+    watchpoints: Vec<Watchpoint>,
+    watch_hit: Option<Watchpoint>,
+    breakpoints: HashSet<usize>,
+    previous_index: Option<usize>,
+    breakpoint_resume: Option<usize>,
+    tracer: Option<TraceWriter>,
+    //File the trace is written to, so a new build can start it again
+    trace_path: Option<PathBuf>,
+    pending_trace: Option<TraceEntry>,
+    input_source: Option<Box<dyn InputSource>>,
+    output_sink: Option<Box<dyn OutputSink>>,
+    config: VmConfig,
+    program_output_len: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Default for InterpreterObject {
+    fn default() -> InterpreterObject {
+        return InterpreterObject::new();
+    }
+}
+
+impl InterpreterObject {
+    pub fn new() -> InterpreterObject {
+        InterpreterObject {
+            input: String::new(),
+            output: String::new(),
+            channels: Vec::new(),
+            token_list: Vec::new(),
+            jump_table: Vec::new(),
+            execute_array: vec![0; VmConfig::default().tape_length],
+            execute_stack: Vec::new(),
+            execute_index: 0,
+            cell_written_at: vec![0; VmConfig::default().tape_length],
+            step_count: 0,
+            token_counts: Vec::new(),
+            skip_counts: Vec::new(),
+            index: 0,
+            config: VmConfig::default(),
+            program_output_len: 0,
+            state: VmState::NotBuilt,
+            input_resume: VmState::Paused,
+            fault: None,
+            history: VecDeque::new(),
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            breakpoints: HashSet::new(),
+            previous_index: None,
+            breakpoint_resume: None,
+            tracer: None,
+            trace_path: None,
+            pending_trace: None,
+            input_source: None,
+            output_sink: None,
+            diagnostics: Vec::new(),
+        }
+    }
+    //Take in a string vector and create a String buffer
+    pub fn populate_input(&mut self, input: &[String]){
+        self.input.clear();
+        self.clear_output();
+        for line in 0..input.len() {
+            self.input += input.get(line).unwrap();
+            if line < input.len()-1 {
+                self.input += "\n";
+            }
+        }
+    }
+    //Take in the source as a single string, lines separated by '\n'
+    pub fn set_source(&mut self, source: &str){
+        self.input.clear();
+        self.clear_output();
+        self.input.push_str(source);
+    }
+    //Get the source text the program is built from, it is left untouched by a build
+    pub fn get_source(&self) -> &String {
+        return &self.input;
+    }
+    //Compile the source into a token list and a jump table.
+    //Every problem in the file is returned as a diagnostic. The program is only executable if there are no errors.
+    pub fn build(&mut self,) -> Vec<Diagnostic> {
+        self.set_state(VmState::NotBuilt);
+        self.fault = None;
+        self.watch_hit = None;
+        self.previous_index = None;
+        self.breakpoint_resume = None;
+        self.restart_trace();
+        self.index = 0;
+        self.execute_index = 0;
+        self.execute_array = vec![0; self.config.tape_length];
+        self.cell_written_at = vec![0; self.config.tape_length];
+        self.execute_stack.clear();
+        self.history.clear();
+        self.step_count = 0;
+        self.program_output_len = 0;
+
+        let (tokens, diagnostics) = Lexer::new(&self.input).tokenize();
+        self.token_list = tokens;
+        self.token_counts = vec![0; self.token_list.len()];
+        self.skip_counts = vec![0; self.token_list.len()];
+        self.diagnostics = diagnostics;
+        self.match_jumps();
+        self.diagnostics.sort_by_key(|item| item.span.start);
+        for item in 0..self.diagnostics.len() {
+            let message = self.diagnostics[item].to_string();
+            self.push_output(Channel::Message, &message);
+            self.push_output(Channel::Message, "\n");
+        }
+
+        let errors = self.diagnostics.iter().filter(|item| item.is_error()).count();
+        if errors == 0 {
+            self.push_output(Channel::Message, "[INFO]: Build Successful\n");
+            self.set_state(VmState::Ready);
+        } else {
+            self.push_output(Channel::Message, &format!("[ERROR]: Build Failure, found {} error(s)\n", errors));
+        }
+        return self.diagnostics.clone();
+    }
+    //Pair every '{' with its '}' and every ':' with its '=', and store where each jump lands.
+    //Unbalanced brackets are reported against the token that is left without a partner
+    fn match_jumps(&mut self,){
+        self.jump_table = vec![0; self.token_list.len()];
+        let mut open_conditionals: Vec<usize> = Vec::new();
+        let mut open_markers: Vec<usize> = Vec::new();
+        for token in 0..self.token_list.len() {
+            match self.token_list[token].kind {
+                CONDITIONALJUMP => {
+                    open_conditionals.push(token);
+                }
+                CONDITIONALMARKER => {
+                    match open_conditionals.pop() {
+                        Some(jump) => self.jump_table[jump] = token + 1,
+                        None => self.report_token(diagnostic::UNMATCHED_CONDITIONAL, "'}' has no matching '{' before it", token),
+                    }
+                }
+                NONCONDITIONALMARKER => {
+                    open_markers.push(token);
+                }
+                NONCONDITIONALJUMP => {
+                    match open_markers.pop() {
+                        Some(marker) => self.jump_table[token] = marker + 1,
+                        None => self.report_token(diagnostic::UNMATCHED_LOOP, "':' has no matching '=' before it", token),
+                    }
+                }
+                _ => {}
+            }
+        }
+        for token in open_conditionals {
+            self.report_token(diagnostic::UNMATCHED_CONDITIONAL, "'{' has no matching '}' after it", token);
+        }
+        for token in open_markers {
+            self.report_token(diagnostic::UNMATCHED_LOOP, "'=' has no matching ':' after it", token);
+        }
+    }
+    //Record a build diagnostic against an already built token
+    fn report_token(&mut self, code: &'static str, message: &str, token: usize){
+        let diagnostic = Diagnostic::error(code, message.to_string(), self.token_list[token].span);
+        self.diagnostics.push(diagnostic);
+    }
+    //Every diagnostic produced by the last build
+    pub fn get_diagnostics(&self) -> &Vec<Diagnostic> {
+        return &self.diagnostics;
+    }
+    //Every token produced by the last build, each with the span of source it came from
+    pub fn get_tokens(&self) -> &Vec<Token> {
+        return &self.token_list;
+    }
+    //Source span of the token that will execute next
+    pub fn current_span(&self) -> Option<Span> {
+        return self.token_list.get(self.index).map(|token| token.span);
+    }
+    
+    //What the interpreter is doing, the editor and runner decide what to offer from it
+    pub fn get_state(&self) -> VmState {
+        return self.state;
+    }
+    //Move to another state, only along the moves VmState allows
+    fn set_state(&mut self, next: VmState){
+        debug_assert!(self.state.can_move_to(next), "{:?} cannot move to {:?}", self.state, next);
+        self.state = next;
+    }
+    //True if there is a built program that has not finished or faulted
+    pub fn is_executable(&self) -> bool {
+        return self.state.is_executable();
+    }
+    pub fn is_waiting(&self) -> bool {
+        return matches!(self.state, VmState::AwaitingInput { .. });
+    }
+    //Run the program from the start, or from where it is paused or waiting for input
+    pub fn start_execution(&mut self,){
+        match self.state {
+            VmState::Ready => self.set_state(VmState::Running),
+            VmState::Paused | VmState::AwaitingInput { .. } => self.resume_execution(),
+            _ => {}
+        }
+    }
+    //True while the program is running, including while it waits for input to carry on running
+    pub fn can_step(&self) -> bool{
+        return self.state == VmState::Running || (self.is_waiting() && self.input_resume == VmState::Running);
+    }
+    //True once execution has reached the end of the token list
+    pub fn is_finished(&self) -> bool{
+        return self.state == VmState::Finished;
+    }
+
+    //Execute a single token of the compiled code, or answer the waiting input token from the input source.
+    //Returns the runtime error if the program halted on it
+    pub fn execute_step(&mut self,) -> Result<(), RuntimeError>{
+        let faulted = self.fault.is_some();
+        self.step();
+        return self.step_result(faulted, ());
+    }
+    fn step(&mut self,){
+        match self.state {
+            VmState::AwaitingInput { .. } => {
+                self.read_input();
+                return;
+            }
+            //Stepping a program that has not started leaves it paused after the token
+            VmState::Ready => self.set_state(VmState::Paused),
+            VmState::Running | VmState::Paused => {}
+            _ => return,
+        }
+        if self.token_list[self.index].kind == ENDOFINPUT {
+            self.finish();
+            return;
+        }
+        self.record_step();
+        self.begin_trace();
+        self.step_count += 1;
+        let current_token = self.token_list[self.index].kind;
+        //Input tokens are counted once their input is accepted
+        if !is_input(current_token) {
+            self.token_counts[self.index] += 1;
+        }
+        self.previous_index = Some(self.index);
+        self.breakpoint_resume = None;
+        let stack_depth = self.execute_stack.len();
+        match current_token {
+           INCREMENT => {
+            self.increment();
+           }
+           DECREMENT => {
+            self.decrement();
+           }
+           SHIFTLEFT => {
+            self.shiftleft();
+           }
+           SHIFTRIGHT => {
+            self.shiftright();
+           }
+           SHIFTNUM => {
+            self.shiftnum();
+           }
+           RESET => {
+            self.reset();
+           }
+           STACKPUSH => {
+            self.stackpush();
+           }
+           STACKPOP => {
+            self.stackpop();
+           }
+           INPUTNUM => {
+            self.inputnum();
+           }
+           INPUTALPHA => {
+            self.inputalpha();
+           }
+           OUTPUTNUM => {
+            self.outputnum();
+           }
+           OUTPUTALPHA => {
+            self.outputalpha();
+           }
+           CONDITIONALJUMP => {
+            self.conditionaljump();
+           }
+           CONDITIONALMARKER => {
+            self.index += 1;
+           }
+           NONCONDITIONALJUMP => {
+            self.nonconditionaljump();
+           }
+           NONCONDITIONALMARKER => {
+            self.index += 1;
+           }
+           _ => {
+            //This should never happen
+            self.fault(RuntimeErrorKind::CorruptTokens);
+           }
+        }
+        if self.execute_stack.len() != stack_depth {
+            self.check_stack_watchpoints();
+        }
+        //Input tokens are traced once the input has been submitted
+        if !self.is_waiting() {
+            self.finish_trace();
+        }
+        self.report_watch_hit();
+        self.check_finished();
+        //Stepping one token at a time, the trace is written out after each
+        if self.state == VmState::Paused {
+            self.flush_trace();
+        }
+    }
+    //Finish as soon as the last token has run, rather than on another step
+    fn check_finished(&mut self,){
+        if matches!(self.state, VmState::Running | VmState::Paused) && self.token_list[self.index].kind == ENDOFINPUT {
+            self.finish();
+        }
+    }
+    fn finish(&mut self,){
+        self.set_state(VmState::Finished);
+        self.push_output(Channel::Message, "\n[INFO]: Finished Execution\n");
+        self.flush_trace();
+    }
+
+    //Execute tokens until the program stops, finishes, or needs input.
+    //Returns the runtime error if the program halted on one
+    pub fn run(&mut self,) -> Result<(), RuntimeError>{
+        self.run_for(usize::MAX)?;
+        return Ok(());
+    }
+
+    //Execute at most 'budget' tokens, stopping early if the program stops, finishes, or needs input.
+    //Returns the number of tokens executed, so the caller can hand control back to its own loop,
+    //or the runtime error if the program halted on one
+    pub fn run_for(&mut self, budget: usize) -> Result<usize, RuntimeError>{
+        let faulted = self.fault.is_some();
+        let mut steps = 0;
+        while steps < budget {
+            if self.is_waiting() && self.input_resume == VmState::Running {
+                self.read_input();
+            }
+            if self.state != VmState::Running {
+                break;
+            }
+            if self.at_breakpoint() {
+                self.pause_at_breakpoint();
+                break;
+            }
+            self.step();
+            steps += 1;
+        }
+        return self.step_result(faulted, steps);
+    }
+
+    //Toggle a breakpoint on a source line, lines start at 1
+    pub fn toggle_breakpoint(&mut self, line: usize){
+        if !self.breakpoints.remove(&line) {
+            self.breakpoints.insert(line);
+        }
+    }
+    pub fn has_breakpoint(&self, line: usize) -> bool{
+        return self.breakpoints.contains(&line);
+    }
+    pub fn clear_breakpoints(&mut self,){
+        self.breakpoints.clear();
+    }
+    //True if running should stop before the current token. A breakpoint line is hit when execution
+    //enters it, from another line or by jumping back, not on every token along the line
+    fn at_breakpoint(&self) -> bool{
+        let line = match self.current_span() {
+            Some(span) => span.line,
+            None => return false,
+        };
+        if !self.breakpoints.contains(&line) || self.breakpoint_resume == Some(self.index) {
+            return false;
+        }
+        match self.previous_index {
+            Some(previous) => return self.token_list[previous].span.line != line || self.index <= previous,
+            None => return true,
+        }
+    }
+    //Pause on a breakpoint, the same breakpoint is not hit again when execution resumes
+    fn pause_at_breakpoint(&mut self,){
+        self.set_state(VmState::Paused);
+        self.breakpoint_resume = Some(self.index);
+        self.flush_trace();
+        if let Some(span) = self.current_span() {
+            self.push_output(Channel::Message, &format!("\n[INFO]: Paused at breakpoint on line {}\n", span.line));
+        }
+    }
+
+    //Pause a running program where it is, leaving the tape, stack and output untouched.
+    //A program paused while waiting for input keeps waiting, and stays paused once the input is answered
+    pub fn pause_execution(&mut self,){
+        if !self.can_step() {
+            return;
+        }
+        if self.is_waiting() {
+            self.input_resume = VmState::Paused;
+        } else {
+            self.set_state(VmState::Paused);
+        }
+        self.flush_trace();
+        self.push_output(Channel::Message, "\n[INFO]: Execution paused\n");
+    }
+    //Continue a paused program from the token it stopped on
+    pub fn resume_execution(&mut self,){
+        if !self.is_paused() {
+            return;
+        }
+        if self.is_waiting() {
+            self.input_resume = VmState::Running;
+        } else {
+            self.set_state(VmState::Running);
+        }
+        self.push_output(Channel::Message, "[INFO]: Execution resumed\n");
+    }
+    //True while the program is paused, including while it waits for input to carry on paused
+    pub fn is_paused(&self) -> bool{
+        return self.state == VmState::Paused || (self.is_waiting() && self.input_resume == VmState::Paused);
+    }
+
+    //Halt execution with a runtime error reported against the current token
+    fn fault(&mut self, kind: RuntimeErrorKind){
+        let error = RuntimeError::new(kind, self.index, self.token_list[self.index].span,
+            self.execute_index, self.execute_array[self.execute_index]);
+        self.push_output(Channel::Message, &format!("[ERROR]: {}\n", error));
+        self.fault = Some(error);
+        self.set_state(VmState::Faulted);
+        self.finish_trace();
+        self.flush_trace();
+    }
+    //The error a stepping call ran into, if the program was not already halted before it
+    fn step_result<T>(&self, faulted: bool, value: T) -> Result<T, RuntimeError> {
+        match &self.fault {
+            Some(error) if !faulted => return Err(error.clone()),
+            _ => return Ok(value),
+        }
+    }
+    //The runtime error that halted the program, if it failed
+    pub fn get_runtime_error(&self) -> Option<&RuntimeError> {
+        return self.fault.as_ref();
+    }
+    //Source span of the token that caused the last runtime error, if the program failed
+    pub fn get_fault_span(&self) -> Option<Span> {
+        return self.fault.as_ref().map(|error| error.span);
+    }
+
+    //Store a value in a cell of the array, remembering the step that changed it
+    fn write_cell(&mut self, cell: usize, value: u32){
+        if let Some(record) = self.history.back_mut() {
+            record.cells.push(CellChange { cell, value: self.execute_array[cell], written_at: self.cell_written_at[cell] });
+        }
+        self.execute_array[cell] = value;
+        self.cell_written_at[cell] = self.step_count;
+        if self.watch_hit.is_none() {
+            self.watch_hit = self.watchpoints.iter().copied().find(|watchpoint| match watchpoint {
+                Watchpoint::CellWrite(watched) => *watched == cell,
+                Watchpoint::CellValue(watched, target) => *watched == cell && *target == value,
+                Watchpoint::StackDepth(_) => false,
+            });
+        }
+    }
+
+    //Remember the state before a token executes, dropping the oldest step once the history is full
+    fn record_step(&mut self,){
+        if self.config.history_limit == 0 {
+            return;
+        }
+        if self.history.len() >= self.config.history_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(StepRecord {
+            index: self.index,
+            pointer: self.execute_index,
+            previous_index: self.previous_index,
+            cells: Vec::new(),
+            stack_len: self.execute_stack.len(),
+            stack_popped: None,
+            output_len: self.output.len(),
+            program_output_len: self.program_output_len,
+        });
+    }
+    //Write every executed token to a JSON Lines trace, replacing any trace already being written
+    pub fn start_trace(&mut self, tracer: TraceWriter){
+        self.stop_trace();
+        self.tracer = Some(tracer);
+    }
+    //Trace every executed token to a new file at the path. Each build starts the file again
+    pub fn start_trace_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let tracer = TraceWriter::create(&path)?;
+        self.start_trace(tracer);
+        self.trace_path = Some(path.as_ref().to_path_buf());
+        return Ok(());
+    }
+    //Stop tracing, flushing whatever has not been written yet
+    pub fn stop_trace(&mut self,){
+        self.flush_trace();
+        self.tracer = None;
+        self.trace_path = None;
+        self.pending_trace = None;
+    }
+    //A trace covers a single build, so step numbers never start over part way through it.
+    //A trace file is started again from empty, a trace to any other writer is stopped
+    fn restart_trace(&mut self,){
+        self.pending_trace = None;
+        if self.tracer.is_none() {
+            return;
+        }
+        let path = self.trace_path.clone();
+        self.stop_trace();
+        match path {
+            Some(path) => {
+                if let Err(error) = self.start_trace_file(&path) {
+                    self.push_output(Channel::Message, &format!("[ERROR]: Trace stopped, unable to create {}: {}\n", path.display(), error));
+                }
+            }
+            None => self.push_output(Channel::Message, "[INFO]: Trace stopped, it only covers the previous build\n"),
+        }
+    }
+    pub fn is_tracing(&self) -> bool{
+        return self.tracer.is_some();
+    }
+    //Note the state before a token executes, if a trace is being written
+    fn begin_trace(&mut self,){
+        if self.tracer.is_none() {
+            return;
+        }
+        let value = self.execute_array[self.execute_index];
+        self.pending_trace = Some(TraceEntry {
+            step: self.step_count + 1,
+            token: self.token_list[self.index],
+            pointer: self.execute_index,
+            before: value,
+            after: value,
+            stack_depth: self.execute_stack.len(),
+        });
+    }
+    //Write the traced token with the state it left behind. A failed write stops the trace, not the program
+    fn finish_trace(&mut self,){
+        let mut entry = match self.pending_trace.take() {
+            Some(entry) => entry,
+            None => return,
+        };
+        entry.after = self.execute_array[entry.pointer];
+        entry.stack_depth = self.execute_stack.len();
+        if let Some(tracer) = self.tracer.as_mut() {
+            if let Err(error) = tracer.write_entry(&entry) {
+                self.push_output(Channel::Message, &format!("\n[ERROR]: Trace stopped, unable to write it: {}\n", error));
+                self.tracer = None;
+            }
+        }
+    }
+    fn flush_trace(&mut self,){
+        if let Some(tracer) = self.tracer.as_mut() {
+            if let Err(error) = tracer.flush() {
+                self.push_output(Channel::Message, &format!("\n[ERROR]: Trace stopped, unable to write it: {}\n", error));
+                self.tracer = None;
+            }
+        }
+    }
+    //Number of steps kept for stepping backwards, 0 turns the history off
+    pub fn set_history_limit(&mut self, limit: usize){
+        self.config.history_limit = limit;
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
+    }
+    //True if there is a recorded step to go back to, a running program must be paused first
+    pub fn can_step_back(&self) -> bool{
+        return !self.can_step() && !self.history.is_empty();
+    }
+    //Undo the last executed token, restoring the array, pointer, stack and output from before it ran
+    pub fn step_back(&mut self,) -> bool{
+        if !self.can_step_back() {
+            return false;
+        }
+        let record = self.history.pop_back().unwrap();
+        for change in record.cells.iter().rev() {
+            self.execute_array[change.cell] = change.value;
+            self.cell_written_at[change.cell] = change.written_at;
+        }
+        match record.stack_popped {
+            Some(value) => self.execute_stack.push(value),
+            None => self.execute_stack.truncate(record.stack_len),
+        }
+        self.truncate_output(record.output_len);
+        self.program_output_len = record.program_output_len;
+        //A '{' that jumped left the index on its jump target
+        if self.token_list[record.index].kind == CONDITIONALJUMP && self.index == self.jump_table[record.index] {
+            self.skip_counts[record.index] -= 1;
+        }
+        //An input token still waiting, or halted by its input, was never counted
+        if !is_input(self.token_list[record.index].kind) || self.index != record.index {
+            self.token_counts[record.index] -= 1;
+        }
+        self.index = record.index;
+        self.execute_index = record.pointer;
+        self.previous_index = record.previous_index;
+        self.step_count -= 1;
+        self.fault = None;
+        self.watch_hit = None;
+        self.set_state(VmState::Paused);
+        return true;
+    }
+    //Move to any step from the oldest one still in the history onwards, stepping back or forwards to reach it.
+    //Returns false if the step could not be reached
+    pub fn jump_to_step(&mut self, step: usize) -> bool{
+        while self.step_count > step {
+            if !self.step_back() {
+                return false;
+            }
+        }
+        while self.step_count < step && matches!(self.state, VmState::Ready | VmState::Paused) {
+            self.step();
+        }
+        return self.step_count == step;
+    }
+    //Step number of the oldest step that can still be returned to
+    pub fn get_oldest_step(&self) -> usize{
+        return self.step_count - self.history.len();
+    }
+
+    //Add a watchpoint, or remove it if it is already set
+    pub fn toggle_watchpoint(&mut self, watchpoint: Watchpoint){
+        match self.watchpoints.iter().position(|item| *item == watchpoint) {
+            Some(position) => {
+                self.watchpoints.remove(position);
+            }
+            None => self.watchpoints.push(watchpoint),
+        }
+    }
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint){
+        if !self.watchpoints.contains(&watchpoint) {
+            self.watchpoints.push(watchpoint);
+        }
+    }
+    pub fn get_watchpoints(&self) -> &Vec<Watchpoint> {
+        return &self.watchpoints;
+    }
+    pub fn clear_watchpoints(&mut self,){
+        self.watchpoints.clear();
+    }
+    //True if any watchpoint looks at the cell
+    pub fn is_watched(&self, cell: usize) -> bool{
+        return self.watchpoints.iter().any(|watchpoint| match watchpoint {
+            Watchpoint::CellWrite(watched) | Watchpoint::CellValue(watched, _) => *watched == cell,
+            Watchpoint::StackDepth(_) => false,
+        });
+    }
+    //Check the stack depth watchpoints after the stack has changed size
+    fn check_stack_watchpoints(&mut self,){
+        if self.watch_hit.is_none() {
+            let depth = self.execute_stack.len();
+            self.watch_hit = self.watchpoints.iter().copied().find(|watchpoint| *watchpoint == Watchpoint::StackDepth(depth));
+        }
+    }
+    //Pause on a watchpoint set off by the last token, naming the token responsible
+    fn report_watch_hit(&mut self,){
+        if let Some(watchpoint) = self.watch_hit.take() {
+            let span = self.token_list[self.previous_index.unwrap_or(self.index)].span;
+            let message = format!("\n[INFO]: Watchpoint hit, {} by '{}' at line {}, column {}\n",
+                watchpoint, &self.input[span.start..span.end], span.line, span.column);
+            self.push_output(Channel::Message, &message);
+            if self.state == VmState::Running {
+                self.set_state(VmState::Paused);
+                self.flush_trace();
+            }
+        }
+    }
+
+    //The execution array, for inspecting memory while stepping
+    pub fn get_tape(&self) -> &Vec<u32> {
+        return &self.execute_array;
+    }
+    //Index of the array cell the program is pointing at
+    pub fn get_pointer(&self) -> usize {
+        return self.execute_index;
+    }
+    //The execution stack, the last item is the top of the stack
+    pub fn get_stack(&self) -> &Vec<u32> {
+        return &self.execute_stack;
+    }
+    //Number of tokens executed since the last build
+    pub fn get_step_count(&self) -> usize {
+        return self.step_count;
+    }
+    //Execution counts of every token, line and loop since the last build
+    pub fn get_profile(&self) -> Profile {
+        let mut loops = Vec::new();
+        for token in 0..self.token_list.len() {
+            if self.token_list[token].kind == NONCONDITIONALJUMP && self.jump_table[token] > 0 {
+                loops.push((self.jump_table[token] - 1, token));
+            }
+        }
+        return Profile::new(&self.token_list, &self.token_counts, &loops);
+    }
+    //Which tokens and '{ }' branches have run since the last build, merge the coverage of several runs to combine them
+    pub fn get_coverage(&self) -> Coverage {
+        return Coverage::new(&self.token_list, &self.token_counts, &self.skip_counts);
+    }
+    //The step on which a cell was last written, None if it has not been written since the build
+    pub fn cell_written_at(&self, cell: usize) -> Option<usize> {
+        match self.cell_written_at.get(cell) {
+            Some(0) | None => return None,
+            Some(step) => return Some(*step),
+        }
+    }
+
+    //Increment the pointed value by 1
+    fn increment(&mut self,){
+        let kind = RuntimeErrorKind::CellAboveMax { max: self.config.max_cell_value };
+        if let Some(value) = self.cell_in_range(self.execute_array[self.execute_index] as i64 + 1, kind) {
+            self.write_cell(self.execute_index, value);
+            self.index += 1;
+        }
+    }
+    //Decrement the pointed value by 1
+    fn decrement(&mut self,){
+        if let Some(value) = self.cell_in_range(self.execute_array[self.execute_index] as i64 - 1, RuntimeErrorKind::CellBelowZero) {
+            self.write_cell(self.execute_index, value);
+            self.index += 1;
+        }
+    }
+    //Shift pointer left (-1)
+    fn shiftleft(&mut self,){
+        if let Some(pointer) = self.pointer_in_range(self.execute_index as i64 - 1, RuntimeErrorKind::PointerBelowZero) {
+            self.execute_index = pointer;
+            self.index += 1;
+        }
+    }
+    //Shift pointer right (+1)
+    fn shiftright(&mut self,){
+        let target = self.execute_index + 1;
+        let kind = RuntimeErrorKind::PointerPastEnd { target: target as u64, last: self.execute_array.len() - 1 };
+        if let Some(pointer) = self.pointer_in_range(target as i64, kind) {
+            self.execute_index = pointer;
+            self.index += 1;
+        }
+    }
+    //Shift pointer to the value at the index
+    fn shiftnum(&mut self,){
+        let target = self.execute_array[self.execute_index];
+        let kind = RuntimeErrorKind::PointerPastEnd { target: target as u64, last: self.execute_array.len() - 1 };
+        if let Some(pointer) = self.pointer_in_range(target as i64, kind) {
+            self.execute_index = pointer;
+            self.index += 1;
+        }
+    }
+    //A new cell value brought into range by the cell overflow policy, None after halting if the policy traps
+    fn cell_in_range(&mut self, value: i64, kind: RuntimeErrorKind) -> Option<u32> {
+        match self.config.cell_overflow.apply(value, self.config.max_cell_value as i64) {
+            Some(value) => return Some(value as u32),
+            None => {
+                self.fault(kind);
+                return None;
+            }
+        }
+    }
+    //A new pointer brought onto the array by the pointer overflow policy, None after halting if the policy traps
+    fn pointer_in_range(&mut self, pointer: i64, kind: RuntimeErrorKind) -> Option<usize> {
+        match self.config.pointer_overflow.apply(pointer, self.execute_array.len() as i64 - 1) {
+            Some(pointer) => return Some(pointer as usize),
+            None => {
+                self.fault(kind);
+                return None;
+            }
+        }
+    }
+    //Set the pointed value to 0
+    fn reset(&mut self,){
+        self.execute_index = 0;
+        self.index += 1;
+    }
+    //Push the pointed value to the stack
+    fn stackpush(&mut self,){
+        if self.execute_stack.len() >= self.config.max_stack_depth {
+            self.fault(RuntimeErrorKind::StackFull { limit: self.config.max_stack_depth });
+        } else {
+            self.execute_stack.push(self.execute_array[self.execute_index]);
+            self.index += 1;
+        }
+    }
+    //Set the pointed value to the popped value of the stack
+    fn stackpop(&mut self,){
+        if self.execute_stack.is_empty() {
+            self.fault(RuntimeErrorKind::StackEmpty);
+        } else {
+            let value = self.execute_stack.pop().unwrap();
+            if let Some(record) = self.history.back_mut() {
+                record.stack_popped = Some(value);
+            }
+            self.write_cell(self.execute_index, value);
+            self.index += 1;
+        }
+    }
+    //Accept input as a number
+    fn inputnum(&mut self,){
+        self.await_input(InputKind::Number);
+    }
+    //Accept input as alphanumeric characters
+    fn inputalpha(&mut self,){
+        self.await_input(InputKind::Text);
+    }
+    //Wait for a line of input, answered straight away if the input source already has one
+    fn await_input(&mut self, kind: InputKind){
+        self.input_resume = self.state;
+        self.set_state(VmState::AwaitingInput { kind });
+        //The trace so far is written out while the program sits waiting
+        if !self.read_input() {
+            self.flush_trace();
+        }
+    }
+    //Output the pointed value as a number
+    fn outputnum(&mut self,){
+        let text = self.execute_array[self.execute_index].to_string();
+        if self.write_output(&text) {
+            self.index += 1;
+        }
+    }
+    //Output the pointed value as a character, halting if it is not one
+    fn outputalpha(&mut self,){
+        let text = match char::from_u32(self.execute_array[self.execute_index]) {
+            Some(character) => character.to_string(),
+            None => {
+                self.fault(RuntimeErrorKind::InvalidCharacter);
+                return;
+            }
+        };
+        if self.write_output(&text) {
+            self.index += 1;
+        }
+    }
+
+    //Read input tokens from a source instead of waiting for submit_input, e.g. a file, stdin or the editor console
+    pub fn set_input_source(&mut self, source: Box<dyn InputSource>){
+        self.input_source = Some(source);
+    }
+    //Go back to waiting for submit_input
+    pub fn clear_input_source(&mut self,){
+        self.input_source = None;
+    }
+    //Send what the program outputs to a sink, leaving only interpreter messages in get_output.
+    //Output already written to a sink is not taken back by stepping backwards
+    pub fn set_output_sink(&mut self, sink: Box<dyn OutputSink>){
+        self.output_sink = Some(sink);
+    }
+    //Go back to writing program output into get_output
+    pub fn clear_output_sink(&mut self,){
+        self.output_sink = None;
+    }
+    //Answer a waiting input token with the next line of the input source.
+    //Returns false if nothing is waiting, there is no source, or it has nothing to read yet
+    pub fn read_input(&mut self,) -> bool{
+        if !self.is_waiting() {
+            return false;
+        }
+        if let Some(sink) = self.output_sink.as_mut() {
+            sink.flush();
+        }
+        //Lines that are not valid input are answered with a message, and the next line is read
+        while self.is_waiting() {
+            let (line, echo) = match self.input_source.as_mut() {
+                Some(source) => (source.read_line(), source.echoes()),
+                None => return false,
+            };
+            match line {
+                Some(line) => {
+                    if self.submit_input(&line, echo) {
+                        return true;
+                    }
+                }
+                None => return false,
+            }
+        }
+        return false;
+    }
+    //Program output goes to the sink if there is one. Returns false, halting the program, if the output limit is reached
+    fn write_output(&mut self, text: &str) -> bool{
+        if self.program_output_len + text.len() > self.config.max_output_size {
+            self.fault(RuntimeErrorKind::OutputLimit { limit: self.config.max_output_size });
+            return false;
+        }
+        self.program_output_len += text.len();
+        match self.output_sink.as_mut() {
+            Some(sink) => sink.write_output(text),
+            None => self.push_output(Channel::Program, text),
+        }
+        return true;
+    }
+    //Jump past the matching '}' if the pointed value is 0
+    fn conditionaljump(&mut self,){
+        if self.execute_array[self.execute_index] == 0 {
+            self.skip_counts[self.index] += 1;
+            self.index = self.jump_table[self.index];
+        }else{
+            self.index += 1;
+        }
+    }
+    //Jump backwards to just after the matching '='
+    fn nonconditionaljump(&mut self,){
+        self.index = self.jump_table[self.index];
+    }
+
+    //Clear everything and rebuild, effectively restarting the execution
+    pub fn reset_execution(&mut self,) {
+        self.set_state(VmState::NotBuilt);
+        self.clear_output();
+        self.build();
+    }
+
+    //Report a message from the editor or runner in the output, alongside the interpreter's own
+    pub fn log_info(&mut self, message: &str){
+        self.push_output(Channel::Message, &format!("\n[INFO]: {}\n", message));
+    }
+
+    //return the output string, everything the console shows with program output, echoed input and messages mixed
+    pub fn get_output(&self,) -> &String{
+        return &self.output;
+    }
+    //Only what was written to one channel, e.g. Channel::Program for exactly what the program printed
+    pub fn get_channel_output(&self, channel: Channel) -> String {
+        return self.get_output_segments().iter().filter(|(segment, _)| *segment == channel).map(|(_, text)| *text).collect();
+    }
+    //The output split into runs of text from the same channel, in the order they were written
+    pub fn get_output_segments(&self) -> Vec<(Channel, &str)> {
+        let mut segments = Vec::new();
+        for item in 0..self.channels.len() {
+            let (start, channel) = self.channels[item];
+            let end = match self.channels.get(item + 1) {
+                Some((next, _)) => *next,
+                None => self.output.len(),
+            };
+            segments.push((channel, &self.output[start..end]));
+        }
+        return segments;
+    }
+    //Add text to the output, starting a new segment when the channel changes
+    fn push_output(&mut self, channel: Channel, text: &str){
+        if text.is_empty() {
+            return;
+        }
+        if self.channels.last().map(|(_, last)| *last) != Some(channel) {
+            self.channels.push((self.output.len(), channel));
+        }
+        self.output.push_str(text);
+    }
+    //Cut the output back to 'length' bytes, dropping the segments past it
+    fn truncate_output(&mut self, length: usize){
+        self.output.truncate(length);
+        while self.channels.last().is_some_and(|(start, _)| *start >= length) {
+            self.channels.pop();
+        }
+    }
+    fn clear_output(&mut self,){
+        self.output.clear();
+        self.channels.clear();
+    }
+
+    //What the waiting input token expects, None if the program is not waiting for input
+    pub fn expected_input(&self) -> Option<InputKind> {
+        match self.state {
+            VmState::AwaitingInput { kind } => return Some(kind),
+            _ => return None,
+        }
+    }
+
+    //Hand a full line of user input to the waiting input token, optionally echoing it to the output.
+    //Returns false if the input was not accepted, the token keeps waiting unless the input halted the program
+    pub fn submit_input(&mut self, entry: &str, echo: bool) -> bool{
+        let kind = match self.state {
+            VmState::AwaitingInput { kind } => kind,
+            _ => return false,
+        };
+        if echo {
+            self.push_output(Channel::Input, entry);
+            self.push_output(Channel::Input, "\n");
+        }
+        let mut number = 0;
+        if kind == InputKind::Number {
+            number = match self.read_number(entry) {
+                Some(number) => number,
+                None => return false,
+            };
+        }else if kind == InputKind::Text && !self.check_text(entry) {
+            return false;
+        }
+        self.set_state(self.input_resume);
+        self.token_counts[self.index] += 1;
+        self.index += 1;
+        if kind == InputKind::Number {
+            self.write_cell(self.execute_index, number);
+        }else if kind == InputKind::Text {
+            self.write_text(entry);
+        }
+        self.finish_trace();
+        self.report_watch_hit();
+        self.check_finished();
+        return true;
+    }
+    //Check a line entered for '?a' is ASCII and fits in the array from the pointer, as laid out by the
+    //string input mode. Asks for another line if it is not ASCII, and halts the program if it does not fit,
+    //or if a value it writes is above the largest value and the cell overflow policy traps
+    fn check_text(&mut self, entry: &str) -> bool {
+        if !entry.is_ascii() {
+            self.push_output(Channel::Message, "[ERROR]: Only ASCII characters can be entered, enter the text again\n");
+            return false;
+        }
+        //The cell the pointer is left on has to be in the array as well
+        let mut end = self.execute_index + entry.len();
+        if self.config.string_input == StringInputMode::LengthPrefixed {
+            end += 1;
+        }
+        if end >= self.execute_array.len() {
+            self.fault(RuntimeErrorKind::TextTooLong { length: entry.len() });
+            return false;
+        }
+        let mut largest = entry.bytes().max().unwrap_or(0) as usize;
+        if self.config.string_input == StringInputMode::LengthPrefixed {
+            largest = largest.max(entry.len());
+        }
+        if self.config.cell_overflow.apply(largest as i64, self.config.max_cell_value as i64).is_none() {
+            self.fault(RuntimeErrorKind::TextAboveMax { value: largest, max: self.config.max_cell_value });
+            return false;
+        }
+        return true;
+    }
+    //Write a line of text into the array from the pointer, leaving the pointer just past it.
+    //Values above the largest value are brought into range by the cell overflow policy
+    fn write_text(&mut self, entry: &str){
+        let policy = self.config.cell_overflow;
+        let max_cell_value = self.config.max_cell_value as i64;
+        let in_range = |value: usize| policy.apply(value as i64, max_cell_value).unwrap_or(0) as u32;
+        let mut cell = self.execute_index;
+        if self.config.string_input == StringInputMode::LengthPrefixed {
+            self.write_cell(cell, in_range(entry.len()));
+            cell += 1;
+        }
+        for character in entry.bytes() {
+            self.write_cell(cell, in_range(character as usize));
+            cell += 1;
+        }
+        if self.config.string_input == StringInputMode::ZeroTerminated {
+            self.write_cell(cell, 0);
+        }
+        self.execute_index = cell;
+    }
+    //Change the limits of the execution environment and how input is read. The tape length takes effect
+    //from the next build, every other setting applies from the next token, even part way through a run
+    pub fn set_config(&mut self, config: VmConfig){
+        self.config = config;
+        self.set_history_limit(config.history_limit);
+    }
+    pub fn get_config(&self) -> &VmConfig {
+        return &self.config;
+    }
+    //Check a number entered for '?0'. Returns None after asking for another number, or halting the program,
+    //if it cannot be stored
+    fn read_number(&mut self, entry: &str) -> Option<u32> {
+        let max_cell_value = self.config.max_cell_value;
+        let digits = entry.trim();
+        if digits.is_empty() || !digits.chars().all(|character| character.is_ascii_digit()) {
+            let message = format!("[ERROR]: '{}' is not a number, enter a whole number from 0 to {}\n", entry, max_cell_value);
+            self.push_output(Channel::Message, &message);
+            return None;
+        }
+        //Too many digits for u64 is out of range all the same
+        let number = digits.parse::<u64>().unwrap_or(u64::MAX);
+        if number <= max_cell_value as u64 {
+            return Some(number as u32);
+        }
+        match self.config.input_range {
+            InputRangePolicy::Reprompt => {
+                let message = format!("[ERROR]: {} is above {}, enter a smaller number\n", digits, max_cell_value);
+                self.push_output(Channel::Message, &message);
+                return None;
+            }
+            InputRangePolicy::Clamp => {
+                let message = format!("[INFO]: {} is above {}, stored {} instead\n", digits, max_cell_value, max_cell_value);
+                self.push_output(Channel::Message, &message);
+                return Some(max_cell_value);
+            }
+            InputRangePolicy::Fault => {
+                self.fault(RuntimeErrorKind::InputAboveMax { input: digits.to_string(), max: max_cell_value });
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OverflowPolicy;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    //An interpreter with the source built
+    fn built(source: &str) -> InterpreterObject {
+        let mut language = InterpreterObject::new();
+        language.set_source(source);
+        language.build();
+        return language;
+    }
+
+    //Trace writer the test can read back while the interpreter still holds it
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            return Ok(buf.len());
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    impl SharedBuffer {
+        fn lines(&self) -> Vec<String> {
+            return String::from_utf8(self.0.lock().unwrap().clone()).unwrap().lines().map(|line| line.to_string()).collect();
+        }
+    }
+
+    //Run a program built with a config until it stops: at the end, on an input token or on a runtime error
+    fn run_to_end(source: &str, config: VmConfig) -> InterpreterObject {
+        let mut language = InterpreterObject::new();
+        language.set_config(config);
+        language.set_source(source);
+        language.build();
+        language.start_execution();
+        language.run().unwrap_or_default();
+        return language;
+    }
+
+    fn traced(source: &str) -> (InterpreterObject, SharedBuffer) {
+        let mut language = built(source);
+        let buffer = SharedBuffer::default();
+        language.start_trace(TraceWriter::new(Box::new(buffer.clone())));
+        return (language, buffer);
+    }
+
+    #[test]
+    fn build_reports_every_error_in_source_order() {
+        let mut language = built("}\n+x{\n?");
+        let found: Vec<(&str, usize, usize)> = language.get_diagnostics().iter().map(|item| (item.code, item.span.line, item.span.column)).collect();
+        assert_eq!(found, vec![
+            (diagnostic::UNMATCHED_CONDITIONAL, 1, 1),
+            (diagnostic::UNRECOGNIZED_CHARACTER, 2, 2),
+            (diagnostic::UNMATCHED_CONDITIONAL, 2, 3),
+            (diagnostic::INVALID_INPUT_TYPE, 3, 1),
+        ]);
+        assert_eq!(language.get_state(), VmState::NotBuilt);
+        assert!(!language.is_executable());
+        let messages = language.get_channel_output(Channel::Message);
+        assert!(messages.contains("[ERROR]: E004 at line 1, column 1: '}' has no matching '{' before it"));
+        assert!(messages.contains("Build Failure, found 4 error(s)"));
+
+        language.set_source("+\n&0");
+        assert!(language.build().is_empty());
+        assert_eq!(language.get_state(), VmState::Ready);
+    }
+
+    #[test]
+    fn interleaved_jumps_pair_by_kind() {
+        //'{' and ':' each pair with the nearest open partner of their own kind, the other kind is ignored
+        let language = built("={:}{=}:");
+        assert!(language.get_diagnostics().is_empty());
+        assert_eq!(language.jump_table, vec![0, 4, 1, 0, 7, 0, 0, 6, 0]);
+    }
+
+    #[test]
+    fn nested_jumps_pair_innermost_first() {
+        let language = built("{{}}==::");
+        assert!(language.get_diagnostics().is_empty());
+        assert_eq!(language.jump_table, vec![4, 3, 0, 0, 0, 0, 6, 5, 0]);
+    }
+
+    #[test]
+    fn unbalanced_jumps_are_reported_against_the_lone_token() {
+        let language = built("}{=\n:{=:}:");
+        let found: Vec<(&str, usize, usize)> = language.get_diagnostics().iter().map(|item| (item.code, item.span.line, item.span.column)).collect();
+        assert_eq!(found, vec![
+            (diagnostic::UNMATCHED_CONDITIONAL, 1, 1),
+            (diagnostic::UNMATCHED_CONDITIONAL, 1, 2),
+            (diagnostic::UNMATCHED_LOOP, 2, 6),
+        ]);
+        assert_eq!(language.get_state(), VmState::NotBuilt);
+
+        let language = built(":");
+        assert_eq!(language.get_diagnostics()[0].message, "':' has no matching '=' before it");
+        let language = built("=");
+        assert_eq!(language.get_diagnostics()[0].message, "'=' has no matching ':' after it");
+    }
+
+    #[test]
+    fn interleaved_loop_and_conditional_run() {
+        //Count down from 3 the way countdown.txt does, the loop ends once '{' on 0 jumps past its '}'
+        let mut language = built("+++={&0-:}&0");
+        assert_eq!(language.jump_table[4], 9);
+        assert_eq!(language.jump_table[7], 4);
+        language.start_execution();
+        language.run().unwrap();
+        assert!(language.is_finished());
+        assert_eq!(language.get_channel_output(Channel::Program), "3210");
+    }
+
+    #[test]
+    fn step_back_restores_cells_stack_and_output() {
+        let mut language = built("++#>+++&0$&0");
+        language.start_execution();
+        language.run().unwrap();
+        assert!(language.is_finished());
+        assert_eq!(language.get_tape()[..2], [2, 2]);
+        assert!(language.get_stack().is_empty());
+        assert_eq!(language.get_channel_output(Channel::Program), "32");
+
+        //Back over the last '&0' and the '$', the popped value goes back on the stack
+        assert!(language.step_back());
+        assert!(language.step_back());
+        assert!(language.is_paused());
+        assert_eq!(language.get_step_count(), 8);
+        assert_eq!(language.get_tape()[..2], [2, 3]);
+        assert_eq!(language.get_stack(), &vec![2]);
+        assert_eq!(language.get_pointer(), 1);
+        assert_eq!(language.get_channel_output(Channel::Program), "3");
+        assert_eq!(language.current_span().unwrap().column, 10);
+    }
+
+    #[test]
+    fn jump_to_step_moves_both_ways() {
+        let mut language = built("++#>+++&0$&0");
+        language.start_execution();
+        language.run().unwrap();
+
+        assert!(language.jump_to_step(3));
+        assert_eq!(language.get_tape()[..2], [2, 0]);
+        assert_eq!(language.get_stack(), &vec![2]);
+        assert_eq!(language.get_pointer(), 0);
+        assert_eq!(language.get_channel_output(Channel::Program), "");
+
+        assert!(language.jump_to_step(8));
+        assert_eq!(language.get_tape()[..2], [2, 3]);
+        assert_eq!(language.get_pointer(), 1);
+        assert_eq!(language.get_channel_output(Channel::Program), "3");
+        assert!(language.is_paused());
+
+        assert!(language.jump_to_step(0));
+        assert_eq!(language.get_tape()[..2], [0, 0]);
+        assert!(language.get_stack().is_empty());
+        assert_eq!(language.get_channel_output(Channel::Program), "");
+        assert!(!language.step_back());
+
+        assert!(language.jump_to_step(10));
+        assert!(language.is_finished());
+        assert_eq!(language.get_channel_output(Channel::Program), "32");
+        //Past the end of the program cannot be reached
+        assert!(!language.jump_to_step(11));
+    }
+
+    #[test]
+    fn pausing_at_input_does_not_run_the_input_token_twice() {
+        let mut language = built("+?0+");
+        language.start_execution();
+        language.run().unwrap();
+        assert_eq!(language.expected_input(), Some(InputKind::Number));
+
+        language.pause_execution();
+        assert!(language.is_paused());
+        assert!(!language.can_step());
+        assert_eq!(language.expected_input(), Some(InputKind::Number));
+        language.resume_execution();
+        assert!(language.can_step());
+        assert!(language.submit_input("5", false));
+        language.run().unwrap();
+        assert!(language.is_finished());
+        assert_eq!(language.get_tape()[0], 6);
+        assert_eq!(language.get_step_count(), 3);
+
+        for _ in 0..3 {
+            assert!(language.step_back());
+        }
+        assert!(!language.step_back());
+        assert_eq!(language.get_step_count(), 0);
+        assert_eq!(language.get_tape()[0], 0);
+    }
+
+    #[test]
+    fn input_answered_while_paused_stays_paused() {
+        let mut language = built("+?0+");
+        language.start_execution();
+        language.run().unwrap();
+        language.pause_execution();
+        //Step back out of the waiting input token, and into it again
+        assert!(language.can_step_back());
+        assert!(language.step_back());
+        assert_eq!(language.get_state(), VmState::Paused);
+        assert_eq!(language.get_step_count(), 1);
+        language.execute_step().unwrap();
+        assert!(language.is_paused());
+        assert_eq!(language.get_step_count(), 2);
+
+        assert!(language.submit_input("5", false));
+        assert_eq!(language.get_state(), VmState::Paused);
+        assert_eq!(language.get_tape()[0], 5);
+        language.execute_step().unwrap();
+        assert!(language.is_finished());
+        assert_eq!(language.get_step_count(), 3);
+        assert_eq!(language.get_tape()[0], 6);
+    }
+
+    #[test]
+    fn trace_is_written_out_when_the_program_faults() {
+        let (mut language, buffer) = traced("+$");
+        language.start_execution();
+        assert!(language.run().is_err());
+        let lines = buffer.lines();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("{\"step\":2,\"token\":\"stack_pop\""));
+    }
+
+    #[test]
+    fn trace_is_written_out_when_the_program_stops() {
+        let (mut language, buffer) = traced("+\n+?0\n+");
+        language.toggle_breakpoint(2);
+        language.start_execution();
+        language.run().unwrap();
+        assert!(language.is_paused());
+        assert_eq!(buffer.lines().len(), 1);
+
+        language.resume_execution();
+        language.run().unwrap();
+        //The input token is written once its input is answered
+        assert!(language.is_waiting());
+        assert_eq!(buffer.lines().len(), 2);
+        assert!(language.submit_input("1", false));
+        language.pause_execution();
+        assert_eq!(buffer.lines().len(), 3);
+
+        language.execute_step().unwrap();
+        assert_eq!(buffer.lines().len(), 4);
+    }
+
+    #[test]
+    fn build_starts_a_trace_file_again() {
+        let path = std::env::temp_dir().join(format!("limitation_trace_{}.jsonl", std::process::id()));
+        let mut language = built("+++");
+        language.start_trace_file(&path).unwrap();
+        language.start_execution();
+        language.run().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        language.set_source("+");
+        language.build();
+        assert!(language.is_tracing());
+        language.start_execution();
+        language.run().unwrap();
+        let trace = fs::read_to_string(&path).unwrap();
+        assert_eq!(trace.lines().count(), 1);
+        assert!(trace.starts_with("{\"step\":1,"));
+        language.stop_trace();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn build_stops_a_trace_to_a_writer() {
+        let (mut language, buffer) = traced("++");
+        language.start_execution();
+        language.run().unwrap();
+        language.reset_execution();
+        assert!(!language.is_tracing());
+        assert!(language.get_output().contains("Trace stopped"));
+        language.start_execution();
+        language.run().unwrap();
+        assert_eq!(buffer.lines().len(), 2);
+    }
+
+    #[test]
+    fn input_tokens_are_counted_once_accepted() {
+        let mut language = built("+?0+");
+        language.start_execution();
+        language.run().unwrap();
+        assert_eq!(language.get_profile().get_token_counts(), &vec![1, 0, 0, 0]);
+        language.pause_execution();
+        language.resume_execution();
+        assert!(!language.submit_input("x", false));
+        assert!(language.submit_input("5", false));
+        language.run().unwrap();
+        assert_eq!(language.get_profile().get_token_counts(), &vec![1, 1, 1, 0]);
+        assert_eq!(language.get_profile().get_total_steps(), 3);
+
+        //Stepping back over the answered input takes its count away, the waiting token had none to take
+        assert!(language.jump_to_step(1));
+        assert_eq!(language.get_profile().get_token_counts(), &vec![1, 0, 0, 0]);
+        language.execute_step().unwrap();
+        assert!(language.is_waiting());
+        assert!(language.step_back());
+        assert!(language.step_back());
+        assert_eq!(language.get_profile().get_token_counts(), &vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn text_input_keeps_to_the_largest_value() {
+        let config = VmConfig { max_cell_value: 100, ..VmConfig::default() };
+        let mut language = run_to_end("?a", config);
+        assert!(!language.submit_input("az", false));
+        assert_eq!(language.get_state(), VmState::Faulted);
+        assert_eq!(language.get_runtime_error().unwrap().kind, RuntimeErrorKind::TextAboveMax { value: 122, max: 100 });
+        assert_eq!(language.get_tape()[..2], [0, 0]);
+
+        let mut language = run_to_end("?a", VmConfig { cell_overflow: OverflowPolicy::Wrap, ..config });
+        assert!(language.submit_input("az", false));
+        assert_eq!(language.get_tape()[..3], [97, 21, 0]);
+
+        let mut language = run_to_end("?a", VmConfig { cell_overflow: OverflowPolicy::Saturate, ..config });
+        assert!(language.submit_input("az", false));
+        assert_eq!(language.get_tape()[..3], [97, 100, 0]);
+    }
+
+    #[test]
+    fn text_length_prefix_keeps_to_the_largest_value() {
+        let text = "a".repeat(130);
+        let config = VmConfig { max_cell_value: 127, string_input: StringInputMode::LengthPrefixed, ..VmConfig::default() };
+        let mut language = run_to_end("?a", config);
+        assert!(!language.submit_input(&text, false));
+        assert_eq!(language.get_runtime_error().unwrap().kind, RuntimeErrorKind::TextAboveMax { value: 130, max: 127 });
+
+        let mut language = run_to_end("?a", VmConfig { cell_overflow: OverflowPolicy::Wrap, ..config });
+        assert!(language.submit_input(&text, false));
+        assert_eq!(language.get_tape()[..2], [2, 97]);
+        assert_eq!(language.get_pointer(), 131);
+    }
+
+    #[test]
+    fn cell_overflow_policies() {
+        let config = VmConfig { max_cell_value: 2, ..VmConfig::default() };
+        let language = run_to_end("+++", config);
+        assert_eq!(language.get_runtime_error().unwrap().kind, RuntimeErrorKind::CellAboveMax { max: 2 });
+        assert_eq!(language.get_tape()[0], 2);
+        let language = run_to_end("-", config);
+        assert_eq!(language.get_runtime_error().unwrap().kind, RuntimeErrorKind::CellBelowZero);
+
+        let wrap = VmConfig { cell_overflow: OverflowPolicy::Wrap, ..config };
+        assert!(run_to_end("+++", wrap).is_finished());
+        assert_eq!(run_to_end("+++", wrap).get_tape()[0], 0);
+        assert_eq!(run_to_end("-", wrap).get_tape()[0], 2);
+
+        let saturate = VmConfig { cell_overflow: OverflowPolicy::Saturate, ..config };
+        assert_eq!(run_to_end("+++++", saturate).get_tape()[0], 2);
+        assert_eq!(run_to_end("+--", saturate).get_tape()[0], 0);
+        assert!(run_to_end("-", saturate).is_finished());
+    }
+
+    #[test]
+    fn pointer_overflow_policies() {
+        let config = VmConfig { tape_length: 4, ..VmConfig::default() };
+        let language = run_to_end("<", config);
+        assert_eq!(language.get_runtime_error().unwrap().kind, RuntimeErrorKind::PointerBelowZero);
+        let language = run_to_end(">>>>", config);
+        assert_eq!(language.get_runtime_error().unwrap().kind, RuntimeErrorKind::PointerPastEnd { target: 4, last: 3 });
+        assert_eq!(language.get_pointer(), 3);
+        let language = run_to_end("+++++^", config);
+        assert_eq!(language.get_runtime_error().unwrap().kind, RuntimeErrorKind::PointerPastEnd { target: 5, last: 3 });
+
+        let wrap = VmConfig { pointer_overflow: OverflowPolicy::Wrap, ..config };
+        assert_eq!(run_to_end("<", wrap).get_pointer(), 3);
+        assert_eq!(run_to_end(">>>>", wrap).get_pointer(), 0);
+        assert_eq!(run_to_end("+++++^", wrap).get_pointer(), 1);
+
+        let saturate = VmConfig { pointer_overflow: OverflowPolicy::Saturate, ..config };
+        assert_eq!(run_to_end("<", saturate).get_pointer(), 0);
+        assert_eq!(run_to_end(">>>>>>", saturate).get_pointer(), 3);
+        assert_eq!(run_to_end("+++++^", saturate).get_pointer(), 3);
+        assert!(run_to_end("+++++^", saturate).is_finished());
+    }
+
+    #[test]
+    fn cell_write_watchpoint_pauses_on_the_writing_token() {
+        let mut language = built("+>+<+");
+        language.add_watchpoint(Watchpoint::CellWrite(1));
+        language.start_execution();
+        language.run().unwrap();
+        assert!(language.is_paused());
+        assert_eq!(language.get_tape()[1], 1);
+        assert_eq!(language.get_step_count(), 3);
+        assert!(language.get_output().contains("[INFO]: Watchpoint hit, cell 1 written by '+' at line 1, column 3"));
+        //Resuming carries on from the token after the write
+        assert_eq!(language.current_span().unwrap().column, 4);
+        language.resume_execution();
+        language.run().unwrap();
+        assert!(language.is_finished());
+        assert_eq!(language.get_tape()[..2], [2, 1]);
+        assert_eq!(language.get_step_count(), 5);
+    }
+
+    #[test]
+    fn cell_value_watchpoint_fires_on_the_value_only() {
+        let mut language = built("+++--");
+        language.add_watchpoint(Watchpoint::CellValue(0, 2));
+        language.start_execution();
+        language.run().unwrap();
+        assert!(language.is_paused());
+        assert_eq!(language.get_step_count(), 2);
+        assert!(language.get_output().contains("cell 0 reached 2 by '+' at line 1, column 2"));
+
+        //3 goes by, then the '-' back down to 2 sets it off again
+        language.resume_execution();
+        language.run().unwrap();
+        assert!(language.is_paused());
+        assert_eq!(language.get_step_count(), 4);
+        assert_eq!(language.get_tape()[0], 2);
+        language.resume_execution();
+        language.run().unwrap();
+        assert!(language.is_finished());
+    }
+
+    #[test]
+    fn stack_depth_watchpoint_fires_on_push_and_pop() {
+        let mut language = built("+#\n#$\n$");
+        language.add_watchpoint(Watchpoint::StackDepth(1));
+        language.start_execution();
+        language.run().unwrap();
+        assert!(language.is_paused());
+        assert_eq!(language.get_stack().len(), 1);
+        assert!(language.get_output().contains("stack depth reached 1 by '#' at line 1, column 2"));
+
+        language.resume_execution();
+        language.run().unwrap();
+        assert!(language.is_paused());
+        assert_eq!(language.get_step_count(), 4);
+        assert!(language.get_output().contains("stack depth reached 1 by '$' at line 2, column 2"));
+
+        language.resume_execution();
+        language.run().unwrap();
+        assert!(language.is_finished());
+        assert!(language.get_stack().is_empty());
+    }
+
+    #[test]
+    fn number_input_reprompts_until_it_fits() {
+        let config = VmConfig { max_cell_value: 100, ..VmConfig::default() };
+        let mut language = run_to_end("?0", config);
+        assert!(!language.submit_input("250", false));
+        assert!(language.get_output().contains("[ERROR]: 250 is above 100, enter a smaller number"));
+        assert!(!language.submit_input("", false));
+        assert!(language.get_output().contains("[ERROR]: '' is not a number, enter a whole number from 0 to 100"));
+        assert!(!language.submit_input("1 2", false));
+        assert_eq!(language.expected_input(), Some(InputKind::Number));
+        assert_eq!(language.get_tape()[0], 0);
+
+        //Whitespace around the number is ignored
+        assert!(language.submit_input("  7\t", false));
+        assert!(language.is_finished());
+        assert_eq!(language.get_tape()[0], 7);
+    }
+
+    #[test]
+    fn number_input_above_the_largest_value_is_clamped() {
+        let config = VmConfig { max_cell_value: 100, input_range: InputRangePolicy::Clamp, ..VmConfig::default() };
+        let mut language = run_to_end("?0", config);
+        assert!(language.submit_input("250", false));
+        assert!(language.is_finished());
+        assert_eq!(language.get_tape()[0], 100);
+        assert!(language.get_channel_output(Channel::Message).contains("[INFO]: 250 is above 100, stored 100 instead"));
+    }
+
+    #[test]
+    fn number_input_above_the_largest_value_faults() {
+        let config = VmConfig { max_cell_value: 100, input_range: InputRangePolicy::Fault, ..VmConfig::default() };
+        let mut language = run_to_end("?0", config);
+        assert!(!language.submit_input("99999999999999999999999", false));
+        assert_eq!(language.get_state(), VmState::Faulted);
+        let error = language.get_runtime_error().unwrap();
+        assert_eq!(error.kind, RuntimeErrorKind::InputAboveMax { input: "99999999999999999999999".to_string(), max: 100 });
+        assert_eq!(language.get_tape()[0], 0);
+        assert!(!language.submit_input("5", false));
+    }
+
+    #[test]
+    fn history_limit_from_the_config_caps_stepping_back() {
+        let config = VmConfig { history_limit: 2, ..VmConfig::default() };
+        let mut language = run_to_end("++++", config);
+        assert!(language.is_finished());
+        assert_eq!(language.get_oldest_step(), 2);
+        assert!(language.step_back());
+        assert!(language.step_back());
+        assert!(!language.step_back());
+        assert_eq!(language.get_tape()[0], 2);
+
+        //A smaller limit drops the oldest steps already kept, and 0 keeps none
+        let mut language = run_to_end("++++", VmConfig::default());
+        assert_eq!(language.get_oldest_step(), 0);
+        language.set_config(VmConfig { history_limit: 1, ..VmConfig::default() });
+        assert_eq!(language.get_oldest_step(), 3);
+        let mut language = run_to_end("++++", VmConfig { history_limit: 0, ..VmConfig::default() });
+        assert!(!language.can_step_back());
+        assert!(!language.step_back());
+    }
+}
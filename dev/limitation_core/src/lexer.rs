@@ -0,0 +1,271 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    lexer.rs
+    Nicholas Soucier
+
+    Lexer for the novelty language. Walks the source by byte offset without modifying it,
+    and gives every token the span of source text it was read from.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::diagnostic;
+use crate::diagnostic::{Diagnostic, Span};
+
+//Tokens
+pub const INCREMENT: u8 = 0;               //+
+pub const DECREMENT: u8 = 1;               //-
+pub const SHIFTLEFT: u8 = 2;               //<
+pub const SHIFTRIGHT: u8 = 3;              //>
+pub const SHIFTNUM: u8 = 4;                //^
+pub const RESET: u8 = 5;                   //_
+pub const STACKPUSH: u8 = 6;               //#
+pub const STACKPOP: u8 = 7;                //$
+pub const INPUTNUM: u8 = 8;                //?0
+pub const INPUTALPHA: u8 = 9;              //?a
+pub const OUTPUTNUM: u8 = 10;              //&0
+pub const OUTPUTALPHA: u8 = 11;            //&a
+pub const CONDITIONALJUMP: u8 = 12;        //{
+pub const CONDITIONALMARKER: u8 = 13;      //}
+pub const NONCONDITIONALJUMP: u8 = 14;     //:
+pub const NONCONDITIONALMARKER: u8 = 15;   //=
+pub const ENDOFINPUT: u8 = 16;             //EOI
+
+//Readable name of a token kind, used in traces and reports
+pub fn token_name(kind: u8) -> &'static str {
+    match kind {
+        INCREMENT => return "increment",
+        DECREMENT => return "decrement",
+        SHIFTLEFT => return "shift_left",
+        SHIFTRIGHT => return "shift_right",
+        SHIFTNUM => return "shift_num",
+        RESET => return "reset",
+        STACKPUSH => return "stack_push",
+        STACKPOP => return "stack_pop",
+        INPUTNUM => return "input_num",
+        INPUTALPHA => return "input_alpha",
+        OUTPUTNUM => return "output_num",
+        OUTPUTALPHA => return "output_alpha",
+        CONDITIONALJUMP => return "conditional_jump",
+        CONDITIONALMARKER => return "conditional_marker",
+        NONCONDITIONALJUMP => return "nonconditional_jump",
+        NONCONDITIONALMARKER => return "nonconditional_marker",
+        ENDOFINPUT => return "end_of_input",
+        _ => return "unknown",
+    }
+}
+
+//True for the tokens that wait for a line of input, '?0' and '?a'
+pub fn is_input(kind: u8) -> bool {
+    return kind == INPUTNUM || kind == INPUTALPHA;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub kind: u8,
+    pub span: Span,
+}
+
+pub struct Lexer<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+    tokens: Vec<Token>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Lexer<'a> {
+        Lexer {
+            source,
+            offset: 0,
+            line: 1,
+            column: 1,
+            tokens: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    //Read the whole source into a token list ending in ENDOFINPUT.
+    //Characters that are not valid tokens are reported and skipped, so every problem is found in one pass
+    pub fn tokenize(mut self) -> (Vec<Token>, Vec<Diagnostic>) {
+        loop {
+            self.ignore_whitespace();
+            let next_char = match self.peek(0) {
+                Some(character) => character,
+                None => {
+                    self.push_token(ENDOFINPUT, 0);
+                    return (self.tokens, self.diagnostics);
+                }
+            };
+            match next_char {
+                '+' => self.push_token(INCREMENT, 1),
+                '-' => self.push_token(DECREMENT, 1),
+                '<' => self.push_token(SHIFTLEFT, 1),
+                '>' => self.push_token(SHIFTRIGHT, 1),
+                '^' => self.push_token(SHIFTNUM, 1),
+                '_' => self.push_token(RESET, 1),
+                '#' => self.push_token(STACKPUSH, 1),
+                '$' => self.push_token(STACKPOP, 1),
+                '?' => {
+                    match self.peek(1) {
+                        Some('0') => self.push_token(INPUTNUM, 2),
+                        Some('a') => self.push_token(INPUTALPHA, 2),
+                        _ => self.report_pair(diagnostic::INVALID_INPUT_TYPE,
+                            "Expected '0' or 'a' after ? token for expected input type"),
+                    }
+                }
+                '&' => {
+                    match self.peek(1) {
+                        Some('0') => self.push_token(OUTPUTNUM, 2),
+                        Some('a') => self.push_token(OUTPUTALPHA, 2),
+                        _ => self.report_pair(diagnostic::INVALID_OUTPUT_TYPE,
+                            "Expected '0' or 'a' after & token for expected output type"),
+                    }
+                }
+                '{' => self.push_token(CONDITIONALJUMP, 1),
+                '}' => self.push_token(CONDITIONALMARKER, 1),
+                ':' => self.push_token(NONCONDITIONALJUMP, 1),
+                '=' => self.push_token(NONCONDITIONALMARKER, 1),
+                _ => {
+                    let message = format!("Unable to recognize character as a token: {}", next_char);
+                    self.report(diagnostic::UNRECOGNIZED_CHARACTER, &message, 1);
+                }
+            }
+        }
+    }
+
+    //Skip whitespace and comments, a lone '/' is reported and skipped
+    fn ignore_whitespace(&mut self) {
+        while let Some(character) = self.peek(0) {
+            if character.is_whitespace() {
+                self.advance();
+            } else if character == '/' {
+                if self.peek(1) == Some('/') {
+                    while let Some(comment) = self.peek(0) {
+                        self.advance();
+                        if comment == '\n' {
+                            break;
+                        }
+                    }
+                } else {
+                    self.report(diagnostic::UNRECOGNIZED_CHARACTER, "Unable to recognize character as a token: /", 1);
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    //Look at the character 'ahead' characters past the current offset
+    fn peek(&self, ahead: usize) -> Option<char> {
+        return self.source[self.offset..].chars().nth(ahead);
+    }
+
+    //Move past the next character, keeping track of the line and column we are on
+    fn advance(&mut self) {
+        if let Some(character) = self.peek(0) {
+            self.offset += character.len_utf8();
+            if character == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+    }
+
+    //Span of the next 'width' characters, which never cross a line
+    fn span(&self, width: usize) -> Span {
+        let length: usize = self.source[self.offset..].chars().take(width).map(|character| character.len_utf8()).sum();
+        return Span::new(self.offset, self.offset + length, self.line, self.column, self.line, self.column + width);
+    }
+
+    //Add a token covering the next 'width' characters, and move past them
+    fn push_token(&mut self, kind: u8, width: usize) {
+        let span = self.span(width);
+        self.tokens.push(Token { kind, span });
+        for _ in 0..width {
+            self.advance();
+        }
+    }
+
+    //Record a diagnostic covering the next 'width' characters, and move past them
+    fn report(&mut self, code: &'static str, message: &str, width: usize) {
+        let span = self.span(width);
+        self.diagnostics.push(Diagnostic::error(code, message.to_string(), span));
+        for _ in 0..width {
+            self.advance();
+        }
+    }
+
+    //Report a malformed '?' or '&' pair. The second character is skipped along with the first,
+    //unless it could start a token of its own, so one typo only produces one error
+    fn report_pair(&mut self, code: &'static str, message: &str) {
+        let skip_second = match self.peek(1) {
+            Some(character) => !character.is_whitespace() && !is_token_start(character),
+            None => false,
+        };
+        if skip_second {
+            self.report(code, message, 2);
+        } else {
+            self.report(code, message, 1);
+        }
+    }
+}
+
+//True if the character begins a token, or a comment
+fn is_token_start(character: char) -> bool {
+    return "+-<>^_#$?&{}:=/".contains(character);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_carry_line_and_column() {
+        let (tokens, diagnostics) = Lexer::new("+ ?0\n  &a").tokenize();
+        assert!(diagnostics.is_empty());
+        let kinds: Vec<u8> = tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(kinds, vec![INCREMENT, INPUTNUM, OUTPUTALPHA, ENDOFINPUT]);
+        assert_eq!((tokens[1].span.line, tokens[1].span.column, tokens[1].span.end_column), (1, 3, 5));
+        assert_eq!((tokens[2].span.line, tokens[2].span.column), (2, 3));
+        assert_eq!((tokens[2].span.start, tokens[2].span.end), (7, 9));
+    }
+
+    #[test]
+    fn every_bad_character_is_reported() {
+        let (tokens, diagnostics) = Lexer::new("+x\n ?b &\n/ + //fine").tokenize();
+        let found: Vec<(&str, usize, usize)> = diagnostics.iter().map(|item| (item.code, item.span.line, item.span.column)).collect();
+        assert_eq!(found, vec![
+            (diagnostic::UNRECOGNIZED_CHARACTER, 1, 2),
+            (diagnostic::INVALID_INPUT_TYPE, 2, 2),
+            (diagnostic::INVALID_OUTPUT_TYPE, 2, 5),
+            (diagnostic::UNRECOGNIZED_CHARACTER, 3, 1),
+        ]);
+        assert!(diagnostics.iter().all(|item| item.is_error()));
+        //The typo "?b" is skipped as one, the '&' before a newline only covers itself
+        assert_eq!(diagnostics[1].span.end_column, 4);
+        assert_eq!(diagnostics[2].span.end_column, 6);
+        //The valid tokens around the errors are still read
+        let kinds: Vec<u8> = tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(kinds, vec![INCREMENT, INCREMENT, ENDOFINPUT]);
+    }
+
+    #[test]
+    fn bad_pair_keeps_a_following_token() {
+        let (tokens, diagnostics) = Lexer::new("?+").tokenize();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span.end_column, 2);
+        assert_eq!(tokens[0].kind, INCREMENT);
+        assert_eq!(tokens[0].span.column, 2);
+    }
+
+    #[test]
+    fn diagnostic_prints_its_place() {
+        let (_, diagnostics) = Lexer::new("\n\n   x").tokenize();
+        assert_eq!(diagnostics[0].to_string(), "[ERROR]: E001 at line 3, column 4: Unable to recognize character as a token: x");
+    }
+}
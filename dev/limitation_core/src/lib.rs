@@ -0,0 +1,37 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    lib.rs
+    Nicholas Soucier
+
+    Library root for the Limitation compiler and executor. Has no windowing dependency so the
+    editor, the command line runner and any other tool can link it.
+    ----------------------------------------------------------------------------------------------
+*/
+
+pub mod config;
+pub mod coverage;
+pub mod diagnostic;
+pub mod error;
+mod history;
+pub mod io;
+pub mod lang;
+pub mod lexer;
+pub mod profile;
+pub mod state;
+pub mod trace;
+pub mod watch;
+pub mod worker;
+
+pub use config::{OverflowPolicy, VmConfig};
+pub use coverage::Coverage;
+pub use diagnostic::{Diagnostic, Severity, Span};
+pub use error::{RuntimeError, RuntimeErrorKind};
+pub use io::{BufferInput, BufferOutput, Channel, ConsoleInput, InputKind, InputRangePolicy, InputSource, OutputSink, ReaderInput, StringInputMode, WriterOutput};
+pub use lang::InterpreterObject;
+pub use lexer::{Lexer, Token};
+pub use profile::{LoopProfile, Profile};
+pub use state::VmState;
+pub use trace::TraceWriter;
+pub use watch::Watchpoint;
+pub use worker::{Worker, WorkerCommand, WorkerEvent};
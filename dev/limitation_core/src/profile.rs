@@ -0,0 +1,124 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    profile.rs
+    Nicholas Soucier
+
+    Execution profile, how many times each token, line and loop ran, and a report of the hot spots.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::diagnostic::Span;
+use crate::lexer::*;
+use std::fmt::Write;
+
+//One '=' .. ':' loop and the work done inside it
+#[derive(Clone, Copy, Debug)]
+pub struct LoopProfile {
+    //The '=' the loop starts from, and the ':' that jumps back to it
+    pub start: Span,
+    pub end: Span,
+    //Times the ':' jumped back
+    pub iterations: usize,
+    //Tokens executed between the '=' and the ':', both included
+    pub steps: usize,
+}
+
+pub struct Profile {
+    token_counts: Vec<usize>,
+    line_counts: Vec<usize>,
+    loops: Vec<LoopProfile>,
+    total_steps: usize,
+}
+
+impl Profile {
+    //Gather the profile from the execution count of every token. 'loops' holds the token index
+    //of each '=' paired with its ':'
+    pub fn new(tokens: &[Token], token_counts: &[usize], loops: &[(usize, usize)]) -> Profile {
+        let lines = tokens.iter().map(|token| token.span.line).max().unwrap_or(0);
+        let mut line_counts = vec![0; lines];
+        for token in 0..tokens.len() {
+            if tokens[token].kind != ENDOFINPUT {
+                line_counts[tokens[token].span.line - 1] += token_counts[token];
+            }
+        }
+        let mut loop_profiles: Vec<LoopProfile> = loops.iter().map(|(marker, jump)| LoopProfile {
+            start: tokens[*marker].span,
+            end: tokens[*jump].span,
+            iterations: token_counts[*jump],
+            steps: token_counts[*marker..=*jump].iter().sum(),
+        }).collect();
+        //Hottest loops first, ties in source order
+        loop_profiles.sort_by(|a, b| b.steps.cmp(&a.steps).then(a.start.start.cmp(&b.start.start)));
+        Profile {
+            token_counts: token_counts.to_vec(),
+            line_counts,
+            loops: loop_profiles,
+            total_steps: token_counts.iter().sum(),
+        }
+    }
+
+    //Times each token ran, in token list order
+    pub fn get_token_counts(&self) -> &Vec<usize> {
+        return &self.token_counts;
+    }
+    //Tokens executed on each source line, line 1 first
+    pub fn get_line_counts(&self) -> &Vec<usize> {
+        return &self.line_counts;
+    }
+    //Tokens executed on a source line, counting lines from 1
+    pub fn get_line_count(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+        return self.line_counts.get(line - 1).copied().unwrap_or(0);
+    }
+    //Most executed line count, used to scale a heatmap
+    pub fn get_max_line_count(&self) -> usize {
+        return self.line_counts.iter().copied().max().unwrap_or(0);
+    }
+    //Every loop in the program, hottest first
+    pub fn get_loops(&self) -> &Vec<LoopProfile> {
+        return &self.loops;
+    }
+    pub fn get_total_steps(&self) -> usize {
+        return self.total_steps;
+    }
+
+    //Plain text report ranking the hot loops and lines, listing at most 'limit' of each
+    pub fn report(&self, limit: usize) -> String {
+        let mut report = String::new();
+        writeln!(report, "Profile: {} token(s) executed", self.total_steps).unwrap_or_default();
+        if self.total_steps == 0 {
+            return report;
+        }
+
+        writeln!(report, "Hot loops:").unwrap_or_default();
+        let hot_loops: Vec<&LoopProfile> = self.loops.iter().filter(|item| item.steps > 0).take(limit).collect();
+        if hot_loops.is_empty() {
+            writeln!(report, "    (no loops executed)").unwrap_or_default();
+        }
+        for (rank, item) in hot_loops.iter().enumerate() {
+            writeln!(report, "    {}. '=' line {}, column {} to ':' line {}, column {}: {} step(s), {:.1}%, {} iteration(s)",
+                rank + 1, item.start.line, item.start.column, item.end.line, item.end.column,
+                item.steps, percent(item.steps, self.total_steps), item.iterations).unwrap_or_default();
+        }
+
+        writeln!(report, "Hot lines:").unwrap_or_default();
+        let mut lines: Vec<usize> = (0..self.line_counts.len()).filter(|line| self.line_counts[*line] > 0).collect();
+        lines.sort_by(|a, b| self.line_counts[*b].cmp(&self.line_counts[*a]).then(a.cmp(b)));
+        for line in lines.iter().take(limit) {
+            writeln!(report, "    line {}: {} step(s), {:.1}%",
+                line + 1, self.line_counts[*line], percent(self.line_counts[*line], self.total_steps)).unwrap_or_default();
+        }
+        return report;
+    }
+}
+
+//Share of a total as a percentage, shared with the coverage report. Nothing out of nothing counts as all of it
+pub(crate) fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    return part as f64 * 100.0 / total as f64;
+}
@@ -0,0 +1,72 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    state.rs
+    Nicholas Soucier
+
+    Execution state of the interpreter, and the moves allowed between states.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::io::InputKind;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VmState {
+    //Nothing built yet, or the last build failed
+    NotBuilt,
+    //Built, no token has executed yet
+    Ready,
+    //Executing tokens until something stops it
+    Running,
+    //Stopped part way, by a breakpoint, a watchpoint, the user, or stepping one token at a time
+    Paused,
+    //An input token is waiting for a line of input
+    AwaitingInput { kind: InputKind },
+    //Reached the end of the program
+    Finished,
+    //Halted on a runtime error
+    Faulted,
+}
+
+impl VmState {
+    //True if the interpreter may move from this state to the next one.
+    //Building moves any state to Ready or NotBuilt
+    pub fn can_move_to(&self, next: VmState) -> bool {
+        if next == VmState::NotBuilt || next == VmState::Ready {
+            return true;
+        }
+        match (self, next) {
+            //Starting, or stepping the first token
+            (VmState::Ready, _) => return true,
+            (VmState::Running, VmState::Paused) | (VmState::Paused, VmState::Running) | (VmState::Paused, VmState::Paused) => return true,
+            (VmState::Running, _) | (VmState::Paused, _) => return matches!(next, VmState::AwaitingInput { .. } | VmState::Finished | VmState::Faulted),
+            //Input answered, the program paused while waiting, or the input halted it
+            (VmState::AwaitingInput { .. }, _) => return matches!(next, VmState::Running | VmState::Paused | VmState::Finished | VmState::Faulted),
+            //Stepping back out of the end of the program
+            (VmState::Finished, VmState::Paused) | (VmState::Faulted, VmState::Paused) => return true,
+            _ => return false,
+        }
+    }
+
+    //True if there is a built program that has not finished or faulted
+    pub fn is_executable(&self) -> bool {
+        return matches!(self, VmState::Ready | VmState::Running | VmState::Paused | VmState::AwaitingInput { .. });
+    }
+}
+
+//Short name shown in the editor, e.g. "Waiting for a number"
+impl fmt::Display for VmState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VmState::NotBuilt => write!(f, "Not built"),
+            VmState::Ready => write!(f, "Ready"),
+            VmState::Running => write!(f, "Running"),
+            VmState::Paused => write!(f, "Paused"),
+            VmState::AwaitingInput { kind: InputKind::Number } => write!(f, "Waiting for a number"),
+            VmState::AwaitingInput { kind: InputKind::Text } => write!(f, "Waiting for text"),
+            VmState::Finished => write!(f, "Finished"),
+            VmState::Faulted => write!(f, "Faulted"),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    trace.rs
+    Nicholas Soucier
+
+    Execution trace, writes one JSON object per executed token (JSON Lines) to any writer.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::lexer::{token_name, Token};
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+//One executed token, as written to the trace
+#[derive(Clone, Copy)]
+pub struct TraceEntry {
+    pub step: usize,
+    pub token: Token,
+    pub pointer: usize,
+    pub before: u32,
+    pub after: u32,
+    pub stack_depth: usize,
+}
+
+pub struct TraceWriter {
+    writer: BufWriter<Box<dyn Write + Send>>,
+}
+
+impl TraceWriter {
+    pub fn new(writer: Box<dyn Write + Send>) -> TraceWriter {
+        TraceWriter {
+            writer: BufWriter::new(writer),
+        }
+    }
+
+    //Trace into a new file, replacing any file already at the path
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<TraceWriter> {
+        let file = File::create(path)?;
+        return Ok(TraceWriter::new(Box::new(file)));
+    }
+
+    //Write a single line, e.g.
+    //{"step":3,"token":"increment","line":1,"column":3,"pointer":0,"before":2,"after":3,"stack_depth":0}
+    pub fn write_entry(&mut self, entry: &TraceEntry) -> io::Result<()> {
+        writeln!(self.writer, "{{\"step\":{},\"token\":\"{}\",\"line\":{},\"column\":{},\"pointer\":{},\"before\":{},\"after\":{},\"stack_depth\":{}}}",
+            entry.step, token_name(entry.token.kind), entry.token.span.line, entry.token.span.column,
+            entry.pointer, entry.before, entry.after, entry.stack_depth)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        return self.writer.flush();
+    }
+}
@@ -0,0 +1,31 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    watch.rs
+    Nicholas Soucier
+
+    Watchpoints, conditions on the array and the stack that pause the program when they are met.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Watchpoint {
+    //Pause whenever the cell is written
+    CellWrite(usize),
+    //Pause when a write leaves the cell holding the value
+    CellValue(usize, u32),
+    //Pause when the stack grows or shrinks to the depth
+    StackDepth(usize),
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Watchpoint::CellWrite(cell) => write!(f, "cell {} written", cell),
+            Watchpoint::CellValue(cell, value) => write!(f, "cell {} reached {}", cell, value),
+            Watchpoint::StackDepth(depth) => write!(f, "stack depth reached {}", depth),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT language core
+    worker.rs
+    Nicholas Soucier
+
+    Runs the interpreter on a worker thread at full speed. The thread owns the interpreter until
+    the program stops, and talks to whoever started it over channels: commands go in, output,
+    input requests and state changes come out.
+    ----------------------------------------------------------------------------------------------
+*/
+
+use crate::io::{Channel, InputKind};
+use crate::lang::InterpreterObject;
+use crate::state::VmState;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::thread::JoinHandle;
+
+//Tokens executed between checks for commands
+const WORKER_SLICE: usize = 100000;
+
+//Sent to the worker
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WorkerCommand {
+    //A line of input for the waiting input token, dropped if no token is waiting
+    Input(String),
+    //Pause the program and hand the interpreter back
+    Stop,
+}
+
+//Sent from the worker
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WorkerEvent {
+    //New text in the interpreter output
+    Output(Channel, String),
+    //An input token is waiting and the input source has nothing for it, answer with WorkerCommand::Input
+    InputRequest(InputKind),
+    //The state once it differs from the last one sent, states passed through in between are not sent
+    StateChanged(VmState),
+}
+
+pub struct Worker {
+    commands: Sender<WorkerCommand>,
+    events: Receiver<WorkerEvent>,
+    thread: JoinHandle<InterpreterObject>,
+}
+
+impl Worker {
+    //Start running a built program on a new thread, from wherever it is.
+    //The thread ends once the program is no longer running: paused, stopped, finished or faulted
+    pub fn spawn(language: InterpreterObject) -> Worker {
+        let (commands, command_receiver) = channel();
+        let (event_sender, events) = channel();
+        let thread = thread::spawn(move || {
+            return run_worker(language, command_receiver, event_sender);
+        });
+        Worker {
+            commands,
+            events,
+            thread,
+        }
+    }
+
+    //Answer an input request
+    pub fn submit_input(&self, line: &str) {
+        self.commands.send(WorkerCommand::Input(line.to_string())).unwrap_or_default();
+    }
+    //Ask the program to pause, the thread ends soon after
+    pub fn stop(&self) {
+        self.commands.send(WorkerCommand::Stop).unwrap_or_default();
+    }
+    //The next event if there is one, without waiting
+    pub fn try_event(&self) -> Option<WorkerEvent> {
+        return self.events.try_recv().ok();
+    }
+    //Wait for the next event, None once the thread has ended and every event has been read
+    pub fn next_event(&self) -> Option<WorkerEvent> {
+        return self.events.recv().ok();
+    }
+    //True once the thread has ended and the interpreter can be taken back with join
+    pub fn is_done(&self) -> bool {
+        return self.thread.is_finished();
+    }
+    //Wait for the thread to end and take the interpreter back. None if the thread panicked
+    pub fn join(self) -> Option<InterpreterObject> {
+        return self.thread.join().ok();
+    }
+}
+
+fn run_worker(mut language: InterpreterObject, commands: Receiver<WorkerCommand>, events: Sender<WorkerEvent>) -> InterpreterObject {
+    let mut sent_output = language.get_output().len();
+    let mut state = language.get_state();
+    language.start_execution();
+    send_changes(&language, &events, &mut sent_output, &mut state);
+    while language.can_step() {
+        match commands.try_recv() {
+            Ok(WorkerCommand::Stop) | Err(TryRecvError::Disconnected) => language.pause_execution(),
+            //Input is only taken while a token is waiting for it
+            Ok(WorkerCommand::Input(line)) => {
+                language.submit_input(&line, true);
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+        match language.get_state() {
+            VmState::Running => {
+                //A runtime error is reported in the output and the state change
+                language.run_for(WORKER_SLICE).unwrap_or_default();
+            }
+            //Ask for input only if the input source has nothing, then wait for it
+            VmState::AwaitingInput { kind } if !language.read_input() => {
+                send_changes(&language, &events, &mut sent_output, &mut state);
+                events.send(WorkerEvent::InputRequest(kind)).unwrap_or_default();
+                match commands.recv() {
+                    Ok(WorkerCommand::Input(line)) => {
+                        language.submit_input(&line, true);
+                    }
+                    Ok(WorkerCommand::Stop) | Err(_) => language.pause_execution(),
+                }
+            }
+            _ => {}
+        }
+        send_changes(&language, &events, &mut sent_output, &mut state);
+    }
+    send_changes(&language, &events, &mut sent_output, &mut state);
+    return language;
+}
+
+//Send the output written since the last call, split by channel, then the state if it changed
+fn send_changes(language: &InterpreterObject, events: &Sender<WorkerEvent>, sent_output: &mut usize, state: &mut VmState) {
+    let mut start = 0;
+    for (channel, text) in language.get_output_segments() {
+        let end = start + text.len();
+        if end > *sent_output {
+            let new_text = &text[(*sent_output).max(start) - start..];
+            events.send(WorkerEvent::Output(channel, new_text.to_string())).unwrap_or_default();
+        }
+        start = end;
+    }
+    *sent_output = start;
+    if language.get_state() != *state {
+        *state = language.get_state();
+        events.send(WorkerEvent::StateChanged(*state)).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(source: &str) -> Worker {
+        let mut language = InterpreterObject::new();
+        language.set_source(source);
+        language.build();
+        return Worker::spawn(language);
+    }
+
+    #[test]
+    fn input_request_is_answered() {
+        let worker = spawn("?0+&0");
+        let mut output = String::new();
+        loop {
+            match worker.next_event() {
+                Some(WorkerEvent::InputRequest(kind)) => {
+                    assert_eq!(kind, InputKind::Number);
+                    worker.submit_input("4");
+                }
+                Some(WorkerEvent::Output(Channel::Program, text)) => output.push_str(&text),
+                Some(_) => {}
+                None => break,
+            }
+        }
+        assert_eq!(output, "5");
+        let language = worker.join().unwrap();
+        assert_eq!(language.get_state(), VmState::Finished);
+        assert_eq!(language.get_channel_output(Channel::Program), "5");
+    }
+
+    #[test]
+    fn stop_pauses_an_endless_loop() {
+        let worker = spawn("=+-:");
+        worker.stop();
+        let mut states = Vec::new();
+        while let Some(event) = worker.next_event() {
+            if let WorkerEvent::StateChanged(state) = event {
+                states.push(state);
+            }
+        }
+        assert_eq!(states.last(), Some(&VmState::Paused));
+        let language = worker.join().unwrap();
+        assert_eq!(language.get_state(), VmState::Paused);
+        assert!(language.is_executable());
+    }
+
+    #[test]
+    fn breakpoint_ends_the_thread_paused() {
+        let mut language = InterpreterObject::new();
+        language.set_source("+\n++\n+");
+        language.build();
+        language.toggle_breakpoint(2);
+        let worker = Worker::spawn(language);
+        while worker.next_event().is_some() {}
+        let language = worker.join().unwrap();
+        assert_eq!(language.get_state(), VmState::Paused);
+        assert_eq!(language.current_span().unwrap().line, 2);
+        assert_eq!(language.get_tape()[0], 1);
+        assert!(language.get_output().contains("Paused at breakpoint on line 2"));
+    }
+}
@@ -0,0 +1,100 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT text editor and compiler
+    background.rs
+    Nicholas Soucier
+
+    code used to run a program on a worker thread while the editor stays responsive. The worker
+    holds the interpreter until the program stops, so the console and ribbon are drawn from what
+    the worker sends back
+    ----------------------------------------------------------------------------------------------
+*/
+
+use limitation_core::{Channel, InterpreterObject, VmConfig, VmState, Worker, WorkerEvent};
+use std::mem;
+
+pub struct BackgroundRun {
+    worker: Worker,
+    //The console as the worker has reported it, starting from the output before the run
+    console: Vec<(Channel, String)>,
+    state: VmState,
+    config: VmConfig,
+    //Messages logged while the worker holds the interpreter, added to its output once it is back
+    messages: Vec<String>,
+}
+
+impl BackgroundRun {
+    //Hand the interpreter to a worker thread, leaving an empty one in its place until finish
+    pub fn start(language: &mut InterpreterObject) -> BackgroundRun {
+        let console = language.get_output_segments().iter().map(|(channel, text)| (*channel, text.to_string())).collect();
+        let state = language.get_state();
+        let config = *language.get_config();
+        let language = mem::replace(language, InterpreterObject::new());
+        BackgroundRun {
+            worker: Worker::spawn(language),
+            console,
+            state,
+            config,
+            messages: Vec::new(),
+        }
+    }
+
+    //Read everything the worker has sent since the last update
+    pub fn update(&mut self) {
+        while let Some(event) = self.worker.try_event() {
+            match event {
+                WorkerEvent::Output(channel, text) => self.push_console(channel, text),
+                WorkerEvent::InputRequest(kind) => self.state = VmState::AwaitingInput { kind },
+                WorkerEvent::StateChanged(state) => self.state = state,
+            }
+        }
+    }
+
+    //Add text to the console, joining it to the last piece if that came from the same channel
+    fn push_console(&mut self, channel: Channel, text: String) {
+        match self.console.last_mut() {
+            Some((last, piece)) if *last == channel => piece.push_str(&text),
+            _ => self.console.push((channel, text)),
+        }
+    }
+
+    //Report a message from the editor, shown in the console now and logged to the interpreter when the run ends
+    pub fn log_info(&mut self, message: &str) {
+        self.push_console(Channel::Message, format!("\n[INFO]: {}\n", message));
+        self.messages.push(message.to_string());
+    }
+
+    pub fn get_state(&self) -> VmState {
+        return self.state;
+    }
+    pub fn get_config(&self) -> &VmConfig {
+        return &self.config;
+    }
+    pub fn get_output_segments(&self) -> Vec<(Channel, &str)> {
+        return self.console.iter().map(|(channel, text)| (*channel, text.as_str())).collect();
+    }
+    pub fn submit_input(&self, line: &str) {
+        self.worker.submit_input(line);
+    }
+    //True once the program has stopped on its own and the interpreter can be taken back
+    pub fn is_done(&self) -> bool {
+        return self.worker.is_done();
+    }
+
+    //Pause the program if it is still running and put the interpreter back, with the messages logged during the run.
+    //Returns false, leaving the empty interpreter in place, if the worker thread panicked
+    pub fn finish(self, language: &mut InterpreterObject) -> bool {
+        self.worker.stop();
+        let returned = match self.worker.join() {
+            Some(worker_language) => {
+                *language = worker_language;
+                true
+            }
+            None => false,
+        };
+        for message in &self.messages {
+            language.log_info(message);
+        }
+        return returned;
+    }
+}
@@ -0,0 +1,82 @@
+/*
+    ----------------------------------------------------------------------------------------------
+    LimitationTEXT text editor and compiler
+    console.rs
+    Nicholas Soucier
+
+    code used to control the console entry, the line the user types while a program waits for input
+    ----------------------------------------------------------------------------------------------
+*/
+
+use piston::input::*;
+
+pub struct ConsoleEntry {
+    entry: String,
+}
+
+impl ConsoleEntry {
+    pub fn new() -> ConsoleEntry {
+        ConsoleEntry {
+            entry: String::new(),
+        }
+    }
+
+    //Get the user input buffer, used for inputnum and inputalpha
+    pub fn get_entry(&self) -> &String {
+        return &self.entry;
+    }
+
+    pub fn clear(&mut self) {
+        self.entry.clear();
+    }
+
+    //Handle keyboard input, returns the finished line once Return is pressed
+    pub fn handle_input(&mut self, key: &Key, shift: &bool) -> Option<String> {
+        if *key == Key::Backspace {
+            if self.entry.len() > 0 {
+                self.entry = self.entry[0..self.entry.len()-1].to_string();
+            }
+        } else if *key == Key::Return {
+            let line = self.entry.clone();
+            self.entry.clear();
+            return Some(line);
+        } else {
+            if *shift {
+                let keychar = getupperchar(&key.code());
+                if keychar != 0 as char {
+                    self.entry.push(keychar);
+                }
+            } else {
+                let keychar = getlowerchar(&key.code());
+                if keychar != 0 as char {
+                    self.entry.push(keychar);
+                }
+            }
+        }
+        return None;
+    }
+}
+
+fn getlowerchar(key: &i32) -> char{
+    match key {
+        97 => return 'a', 98 => return 'b', 99 => return 'c', 100 => return 'd', 101 => return 'e', 102 => return 'f', 103 => return 'g',
+        104 => return 'h', 105 => return 'i', 106 => return 'j', 107 => return 'k', 108 => return 'l', 109 => return 'm',
+        110 => return 'n', 111 => return 'o', 112 => return 'p', 113 => return 'q', 114 => return 'r', 115 => return 's',
+        116 => return 't', 117 => return 'u', 118 => return 'v', 119 => return 'w', 120 => return 'x', 121 => return 'y',
+        122 => return 'z', 48 => return '0', 49 => return '1', 50 => return '2', 51 => return '3', 52 => return '4', 53 => return '5',
+        54 => return '6', 55 => return '7', 56 => return '8', 57 => return '9', 45 => return '-',  46 => return '.',
+        32 => return ' ',
+        _ => return 0 as char,
+    }
+}
+
+fn getupperchar(key: &i32) -> char{
+    match key {
+        97 => return 'A', 98 => return 'B', 99 => return 'C', 100 => return 'D', 101 => return 'E', 102 => return 'F', 103 => return 'G',
+        104 => return 'H', 105 => return 'I', 106 => return 'J', 107 => return 'K', 108 => return 'L', 109 => return 'M',
+        110 => return 'N', 111 => return 'O', 112 => return 'P', 113 => return 'Q', 114 => return 'R', 115 => return 'S',
+        116 => return 'T', 117 => return 'U', 118 => return 'V', 119 => return 'W', 120 => return 'X', 121 => return 'Y',
+        122 => return 'Z', 45 => return '_', 32 => return ' ',
+        _ => return 0 as char,
+    }
+}
@@ -23,6 +23,7 @@ use std::io::*;
 use std::path::*;
 use std::fs::*;
 use std::env;
+use std::time::{Duration, Instant};
 
 //Colors as f32 arrays, color references for drawing.
 pub struct Palette {
@@ -32,6 +33,35 @@ pub struct Palette {
     highlight: [f32; 4],
     disable_highlight: [f32; 4],
     text: [f32; 4],
+    recent_change: [f32; 4],
+    error_highlight: [f32; 4],
+    breakpoint: [f32; 4],
+    heatmap: [f32; 4],
+    console_input: [f32; 4],
+    console_message: [f32; 4],
+}
+
+//Tokens executed between checks of the frame time budget
+const EXECUTION_SLICE: usize = 1000;
+//Time the program may run for in each update, before the window gets control back
+const EXECUTION_FRAME_BUDGET: Duration = Duration::from_millis(8);
+//Steps after a write during which the memory inspector still marks a cell as changed
+const RECENT_CHANGE_STEPS: usize = 16;
+//Loops and lines listed in the hot spot report
+const PROFILE_REPORT_LENGTH: usize = 5;
+//VM settings file, applied on every build. It can be opened and edited like any other save
+const VM_CONFIG_PATH: &str = "saves/limitation.cfg";
+//Layout of the memory inspector rows
+const INSPECTOR_TOP: f64 = 80.0;
+const INSPECTOR_ROW_HEIGHT: f64 = 12.0;
+
+//What the memory inspector prompt asks for, typed as a number and set with Return
+#[derive(Clone, Copy, PartialEq)]
+enum WatchPrompt {
+    //The value to watch a cell for
+    CellValue(usize),
+    //The stack depth to watch for
+    StackDepth,
 }
 
 //External Rust files and their objects
@@ -39,8 +69,11 @@ mod save;
 use save::SaveWindow;
 mod open;
 use open::OpenWindow;
-mod lang;
-use lang::InterpreterObject;
+mod console;
+use console::ConsoleEntry;
+mod background;
+use background::BackgroundRun;
+use limitation_core::{Channel, ConsoleInput, InputKind, InterpreterObject, Span, StringInputMode, VmConfig, VmState, Watchpoint};
 
 //Struct for icons to draw to screen
 pub struct Icons {
@@ -49,6 +82,7 @@ pub struct Icons {
     buildicon: G2dTexture,
     executeicon: G2dTexture,
     stepicon: G2dTexture,
+    stopexecutionicon: G2dTexture,
     resetexecutionicon: G2dTexture,
     helpicon: G2dTexture,
 }
@@ -72,6 +106,12 @@ fn main(){
         highlight: [0.4, 0.435, 0.502, 0.75],
         disable_highlight: [0.233, 0.252, 0.295, 0.5],
         text: [0.914, 0.918, 0.929, 1.0],
+        recent_change: [0.957, 0.780, 0.408, 1.0],
+        error_highlight: [0.749, 0.263, 0.263, 0.75],
+        breakpoint: [0.859, 0.298, 0.298, 1.0],
+        heatmap: [0.957, 0.580, 0.208, 0.6],
+        console_input: [0.596, 0.765, 0.475, 1.0],
+        console_message: [0.612, 0.639, 0.698, 1.0],
     };
 
     //Get the file path of the executable and create a directory if needed
@@ -99,6 +139,7 @@ fn main(){
      let buildicon_file = assets.join("buildicon.png");
      let executeicon_file = assets.join("executeicon.png");
      let stepicon_file = assets.join("stepicon.png");
+     let stopexecutionicon_file = assets.join("stopexecutionicon.png");
      let resetexecutionicon_file = assets.join("resetexecutionicon.png");
      let helpicon_file = assets.join("helpicon.png");
      let icons = Icons {
@@ -107,6 +148,7 @@ fn main(){
         buildicon: Texture::from_path(&mut window.create_texture_context(), &buildicon_file, Flip::None, &TextureSettings::new()).unwrap(),
         executeicon: Texture::from_path(&mut window.create_texture_context(), &executeicon_file, Flip::None, &TextureSettings::new()).unwrap(),
         stepicon: Texture::from_path(&mut window.create_texture_context(), &stepicon_file, Flip::None, &TextureSettings::new()).unwrap(),
+        stopexecutionicon: Texture::from_path(&mut window.create_texture_context(), &stopexecutionicon_file, Flip::None, &TextureSettings::new()).unwrap(),
         resetexecutionicon: Texture::from_path(&mut window.create_texture_context(), &resetexecutionicon_file, Flip::None, &TextureSettings::new()).unwrap(),
         helpicon: Texture::from_path(&mut window.create_texture_context(), &helpicon_file, Flip::None, &TextureSettings::new()).unwrap(),
     };
@@ -130,11 +172,23 @@ fn main(){
     let mut display_save_window = false;
     let mut display_open_window = false;
     let mut display_help_window = false;
+    let mut display_inspector_window = false;
+    let mut display_heatmap = false;
+    //Number being typed for a value or stack depth watchpoint in the memory inspector
+    let mut watch_prompt: Option<WatchPrompt> = None;
+    let mut watch_entry = ConsoleEntry::new();
 
     //Event variables
     let mut mousecursor = [0.0; 2];
     let mut holdbutton: Vec<[f64; 2]> = Vec::new();
     let mut language_interpreter = InterpreterObject::new();
+    let mut console_entry = ConsoleEntry::new();
+    //Lines entered in the console answer the program's input tokens
+    let console_input = ConsoleInput::new();
+    language_interpreter.set_input_source(Box::new(console_input.clone()));
+    //Programs run on a worker thread instead of between frames while this is on, toggled with CTRL+R
+    let mut background_execution = false;
+    let mut background: Option<BackgroundRun> = None;
 
     //Event loop
     while let Some(e) = window.next() {
@@ -152,21 +206,46 @@ fn main(){
             image(&icons.buildicon, c.transform.trans(128.0, 0.0), g);
             image(&icons.executeicon, c.transform.trans(192.0, 0.0), g);
             image(&icons.stepicon, c.transform.trans(256.0, 0.0), g);
-            image(&icons.resetexecutionicon, c.transform.trans(320.0, 0.0), g);
-            image(&icons.helpicon, c.transform.trans(384.0, 0.0), g);
+            image(&icons.stopexecutionicon, c.transform.trans(320.0, 0.0), g);
+            image(&icons.resetexecutionicon, c.transform.trans(384.0, 0.0), g);
+            image(&icons.helpicon, c.transform.trans(448.0, 0.0), g);
             //Draw ribbon mouse-over highlight
-            draw_ribbon_highlight(&c, g, &palette, &mousecursor, &language_interpreter);   
-            //Draw text
-            draw_input_buffer_updated(&c, g, &input_lines, &palette, &mut glyphs, &cursorpos, &windowsize); 
+            let (vm_state, vm_running) = get_vm_status(&background, &language_interpreter);
+            draw_ribbon_highlight(&c, g, &palette, &mousecursor, vm_state, vm_running);   
+            draw_ribbon_state(&c, g, vm_state, &palette, &mut glyphs);
+            //Draw text, the interpreter is on the worker thread during a background run
+            if display_heatmap && background.is_none() {
+                draw_profile_heatmap(&c, g, &language_interpreter, &input_lines, &palette, &cursorpos, &windowsize);
+            }
+            let mut token_highlight = None;
+            if background.is_none() {
+                token_highlight = get_token_highlight(&language_interpreter, &palette);
+            }
+            draw_input_buffer_updated(&c, g, &input_lines, &palette, &mut glyphs, &cursorpos, &windowsize, &token_highlight); 
+            if background.is_none() {
+                draw_breakpoint_gutter(&c, g, &language_interpreter, &input_lines, &palette, &cursorpos, &windowsize);
+            }
             glyphs.factory.encoder.flush(device); 
 
             if display_help_window {
                 draw_help_window(&c, g, &palette, &windowsize);
-                draw_help_window_text(&c, g, &palette, &mut glyphs, &windowsize);
+                draw_help_window_text(&c, g, &language_interpreter, &palette, &mut glyphs, &windowsize);
+            }
+            if display_inspector_window {
+                draw_help_window(&c, g, &palette, &windowsize);
+                if background.is_none() {
+                    let prompt = watch_prompt.map(|prompt| get_watch_prompt_text(prompt, watch_entry.get_entry()));
+                    draw_inspector_text(&c, g, &language_interpreter, &palette, &mut glyphs, &windowsize, prompt);
+                }
             }
 
             draw_output_console(&c, g, &palette, &windowsize);
-            draw_output_buffer(&c, g, &mut language_interpreter, &palette, &mut glyphs, &windowsize);
+            match &background {
+                Some(run) => draw_output_buffer(&c, g, run.get_output_segments(), run.get_state(), run.get_config(),
+                    console_entry.get_entry(), &palette, &mut glyphs, &windowsize),
+                None => draw_output_buffer(&c, g, language_interpreter.get_output_segments(), language_interpreter.get_state(),
+                    language_interpreter.get_config(), console_entry.get_entry(), &palette, &mut glyphs, &windowsize),
+            }
 
             //Draw Extra Windows
             if display_save_window {
@@ -201,40 +280,86 @@ fn main(){
                 }
                 //Build Code Button
                 else if mousecursor[0] > 128.0 && mousecursor[0] < 192.0 {
+                    stop_background(&mut background, &mut language_interpreter, &console_input);
                     language_interpreter.populate_input(&input_lines);
+                    let config_error = load_vm_config(&mut language_interpreter);
                     language_interpreter.build();
+                    if let Some(message) = config_error {
+                        language_interpreter.log_info(&message);
+                    }
+                    console_entry.clear();
                 }
                 //Execute Code Button
                 else if mousecursor[0] > 192.0 && mousecursor[0] < 256.0 {
-                    if language_interpreter.is_executable() == true {
+                    if is_ribbon_button_enabled(get_vm_status(&background, &language_interpreter), 3) {
                         language_interpreter.start_execution();
                     }
                 }
                 //Execute By Step BUtton
                 else if mousecursor[0] > 256.0 && mousecursor[0] < 320.0 {
-                    if language_interpreter.is_executable() == true {
-                        language_interpreter.execute_step();
+                    if is_ribbon_button_enabled(get_vm_status(&background, &language_interpreter), 4) {
+                        //A runtime error is already reported in the console and highlighted in the source
+                        language_interpreter.execute_step().unwrap_or_default();
                     }
                 }
-                //Reset Execution Button
+                //Stop/Resume Execution Button
                 else if mousecursor[0] > 320.0 && mousecursor[0] < 384.0 {
+                    if stop_background(&mut background, &mut language_interpreter, &console_input) {
+                        console_entry.clear();
+                    } else if language_interpreter.is_paused() {
+                        language_interpreter.resume_execution();
+                    } else if language_interpreter.can_step() {
+                        language_interpreter.pause_execution();
+                    }
+                }
+                //Reset Execution Button
+                else if mousecursor[0] > 384.0 && mousecursor[0] < 448.0 {
+                    stop_background(&mut background, &mut language_interpreter, &console_input);
+                    let config_error = load_vm_config(&mut language_interpreter);
                     language_interpreter.reset_execution();
+                    if let Some(message) = config_error {
+                        language_interpreter.log_info(&message);
+                    }
+                    console_entry.clear();
                 }
                 //Quick Reference Button
-                else if mousecursor[0] > 384.0 && mousecursor[0] < 448.0 {
+                else if mousecursor[0] > 448.0 && mousecursor[0] < 512.0 {
                     if display_help_window{
                         display_help_window = false;
                     }else{
                         display_help_window = true;
+                        display_inspector_window = false;
+                        watch_prompt = None;
                     }
                 }
                 else{
                     display_open_window = false;
                     display_save_window = false;
                 }
-            }else{
+            }else if background.is_none() {
                 display_save_window = false;
                 display_open_window = false;
+                //Memory inspector, clicking a cell watches it for writes, SHIFT+click asks for a value to watch it for,
+                //and clicking the stack header asks for a depth to watch the stack for
+                if display_inspector_window {
+                    if let Some(cell) = get_inspector_cell_at(&mousecursor, &language_interpreter, &windowsize) {
+                        if shift == true {
+                            watch_prompt = Some(WatchPrompt::CellValue(cell));
+                            watch_entry.clear();
+                        } else {
+                            language_interpreter.toggle_watchpoint(Watchpoint::CellWrite(cell));
+                        }
+                    } else if is_inspector_stack_header_at(&mousecursor, &language_interpreter, &windowsize) {
+                        watch_prompt = Some(WatchPrompt::StackDepth);
+                        watch_entry.clear();
+                    }
+                }
+                //Breakpoint gutter, left of the text
+                if mousecursor[0] < 18.0 {
+                    if let Some(line) = get_line_at(&mousecursor, &input_lines, &cursorpos, &windowsize) {
+                        language_interpreter.toggle_breakpoint(line + 1);
+                    }
+                }
             }
         }
         //Keyboard key press event
@@ -247,19 +372,79 @@ fn main(){
             else if key == Key::LCtrl || key == Key::RCtrl {
                 control = true;
             }
-            //Escape
+            //Escape : Close windows and pause a running program
             else if key == Key::Escape {
                 display_save_window = false;
                 display_open_window = false;
                 display_help_window = false;
+                display_inspector_window = false;
+                watch_prompt = None;
+                if !stop_background(&mut background, &mut language_interpreter, &console_input) {
+                    language_interpreter.pause_execution();
+                }
+                console_entry.clear();
             }
             //Keyboard shortcut: CTRL+N : Creates new file as if program was just opened.
             else if control == true && key == Key::N {
+                stop_background(&mut background, &mut language_interpreter, &console_input);
                 filename = String::from("Untitled.txt");
                 input_lines.clear();
                 cursorpos[0] = 0;
                 cursorpos[1] = 0;
                 language_interpreter.reset_execution();
+                console_entry.clear();
+            }
+            //Keyboard shortcut: CTRL+M : Show or hide the memory inspector
+            else if control == true && key == Key::M {
+                display_inspector_window = !display_inspector_window;
+                watch_prompt = None;
+                if display_inspector_window {
+                    display_help_window = false;
+                }
+            }
+            //Keyboard shortcut: CTRL+H : Show or hide the execution heatmap, printing the hot spot report when shown
+            else if control == true && key == Key::H {
+                stop_background(&mut background, &mut language_interpreter, &console_input);
+                display_heatmap = !display_heatmap;
+                if display_heatmap {
+                    let report = language_interpreter.get_profile().report(PROFILE_REPORT_LENGTH);
+                    language_interpreter.log_info(report.trim_end());
+                }
+            }
+            //Keyboard shortcut: CTRL+B : Step back one token, CTRL+SHIFT+B : Rewind to the oldest recorded step
+            else if control == true && key == Key::B {
+                stop_background(&mut background, &mut language_interpreter, &console_input);
+                language_interpreter.pause_execution();
+                console_entry.clear();
+                if shift == true {
+                    let oldest_step = language_interpreter.get_oldest_step();
+                    language_interpreter.jump_to_step(oldest_step);
+                } else {
+                    language_interpreter.step_back();
+                }
+            }
+            //Keyboard shortcut: CTRL+T : Start or stop writing an execution trace next to the open file
+            else if control == true && key == Key::T {
+                stop_background(&mut background, &mut language_interpreter, &console_input);
+                if language_interpreter.is_tracing() {
+                    language_interpreter.stop_trace();
+                    language_interpreter.log_info("Trace stopped");
+                } else {
+                    let tracepath = get_trace_path(&filename);
+                    match language_interpreter.start_trace_file(&tracepath) {
+                        Ok(_) => language_interpreter.log_info(&format!("Tracing execution to {}", tracepath)),
+                        Err(error) => language_interpreter.log_info(&format!("Unable to create {}: {}", tracepath, error)),
+                    }
+                }
+            }
+            //Keyboard shortcut: CTRL+R : Run programs on a worker thread, or between frames
+            else if control == true && key == Key::R {
+                background_execution = !background_execution;
+                if background_execution {
+                    log_info(&mut background, &mut language_interpreter, "Programs will run on a background thread");
+                } else {
+                    log_info(&mut background, &mut language_interpreter, "Programs will run in the editor between frames");
+                }
             }
             //Keyboard shortcut: CTRL+S : Quick-save, or open save window
             else if control == true && key == Key::S {
@@ -304,9 +489,27 @@ fn main(){
                     } else {
                         open_window.handle_input(&key);
                     }
+                //Input for the memory inspector prompt
+                } else if let (Some(prompt), true) = (watch_prompt, background.is_none()) {
+                    if let Some(line) = watch_entry.handle_input(&key, &shift) {
+                        watch_prompt = None;
+                        match (prompt, line.trim().parse::<u32>()) {
+                            (WatchPrompt::CellValue(cell), Ok(value)) => language_interpreter.toggle_watchpoint(Watchpoint::CellValue(cell, value)),
+                            (WatchPrompt::StackDepth, Ok(depth)) => language_interpreter.toggle_watchpoint(Watchpoint::StackDepth(depth as usize)),
+                            (_, Err(_)) => language_interpreter.log_info(&format!("'{}' is not a number, no watchpoint was set", line)),
+                        }
+                    }
                 //Input for output window
-                } else if language_interpreter.is_waiting() {
-                    language_interpreter.handle_input(&key, &shift);
+                } else if let (VmState::AwaitingInput { .. }, _) = get_vm_status(&background, &language_interpreter) {
+                    if let Some(line) = console_entry.handle_input(&key, &shift) {
+                        match &background {
+                            Some(run) => run.submit_input(&line),
+                            None => {
+                                console_input.submit(&line);
+                                language_interpreter.read_input();
+                            }
+                        }
+                    }
                 } 
                 //Input for text editor
                 else {
@@ -323,19 +526,33 @@ fn main(){
                     if display_save_window == true {
                         save_window.handle_input(&Key::from(holdbutton[item][0] as u32), &shift);
                     }
-                    else if display_save_window == true {
+                    else if display_open_window == true {
                         open_window.handle_input(&Key::from(holdbutton[item][0] as u32));
-                    } else {
+                    }
+                    //Keys held in the memory inspector prompt are not repeated into the text
+                    else if watch_prompt.is_none() {
                         handle_input(&mut input_lines, &Key::from(holdbutton[item][0] as u32), 
                             &mut cursorpos, &shift, &control);
                     }
                     holdbutton[item][1] = 0.39;
                 }
             }
-            if language_interpreter.can_step() && language_interpreter.is_waiting() == false {
-                language_interpreter.execute_step();
-                while language_interpreter.can_recur_step(){
-                    language_interpreter.execute_step();
+            //A background run hands the interpreter back once the program stops
+            if let Some(run) = background.as_mut() {
+                run.update();
+                if run.is_done() {
+                    stop_background(&mut background, &mut language_interpreter, &console_input);
+                }
+            } else if background_execution && language_interpreter.can_step() {
+                background = Some(BackgroundRun::start(&mut language_interpreter));
+            } else {
+                //Run the program in slices under a time budget, so an endless loop cannot freeze the window
+                let frame_start = Instant::now();
+                loop {
+                    match language_interpreter.run_for(EXECUTION_SLICE) {
+                        Ok(steps) if steps == EXECUTION_SLICE && frame_start.elapsed() < EXECUTION_FRAME_BUDGET => {}
+                        _ => break,
+                    }
                 }
             }
         }
@@ -380,7 +597,7 @@ fn main(){
 }
 
 //Draw the top ribbon highlights, when the mouse intersects with the ribbon
-fn draw_ribbon_highlight<G: Graphics>(c: &Context, g: &mut G, colors: &Palette, mousecursor: &[f64; 2], language_interpreter: &InterpreterObject){
+fn draw_ribbon_highlight<G: Graphics>(c: &Context, g: &mut G, colors: &Palette, mousecursor: &[f64; 2], state: VmState, running: bool){
     if mousecursor[1] < 64.0 {
         if mousecursor[0] > 0.0 && mousecursor[0] < 64.0 {
             Rectangle::new(colors.highlight).draw([0.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
@@ -389,59 +606,146 @@ fn draw_ribbon_highlight<G: Graphics>(c: &Context, g: &mut G, colors: &Palette,
         }else if mousecursor[0] > 128.0 && mousecursor[0] < 192.0 {
             Rectangle::new(colors.highlight).draw([128.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
         }else if mousecursor[0] > 192.0 && mousecursor[0] < 256.0 {
-            if language_interpreter.is_executable() {
+            if is_ribbon_button_enabled((state, running), 3) {
                 Rectangle::new(colors.highlight).draw([192.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
             } else {
                 Rectangle::new(colors.disable_highlight).draw([192.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
             }
         }else if mousecursor[0] > 256.0 && mousecursor[0] < 320.0 {
-            if language_interpreter.is_executable() {
+            if is_ribbon_button_enabled((state, running), 4) {
                 Rectangle::new(colors.highlight).draw([256.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
             } else {
                 Rectangle::new(colors.disable_highlight).draw([256.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
             }       
         }else if mousecursor[0] > 320.0 && mousecursor[0] < 384.0 {
-            if language_interpreter.is_executable() {
+            if is_ribbon_button_enabled((state, running), 5) {
                 Rectangle::new(colors.highlight).draw([320.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
             } else {
                 Rectangle::new(colors.disable_highlight).draw([320.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
-            }        
+            }
         }else if mousecursor[0] > 384.0 && mousecursor[0] < 448.0 {
-            Rectangle::new(colors.highlight).draw([384.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
+            if is_ribbon_button_enabled((state, running), 6) {
+                Rectangle::new(colors.highlight).draw([384.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
+            } else {
+                Rectangle::new(colors.disable_highlight).draw([384.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
+            }        
+        }else if mousecursor[0] > 448.0 && mousecursor[0] < 512.0 {
+            Rectangle::new(colors.highlight).draw([448.0, 0.0, 64.0, 64.0], &c.draw_state, c.transform, g);
         }
     }
 }
 
+//The interpreter's state, and whether its program is running, from the worker during a background run
+fn get_vm_status(background: &Option<BackgroundRun>, language: &InterpreterObject) -> (VmState, bool) {
+    match background {
+        Some(run) => return (run.get_state(), true),
+        None => return (language.get_state(), language.can_step()),
+    }
+}
+
+//Pause a background run and take the interpreter back from the worker. Returns false if there was no run
+fn stop_background(background: &mut Option<BackgroundRun>, language: &mut InterpreterObject, console_input: &ConsoleInput) -> bool {
+    let run = match background.take() {
+        Some(run) => run,
+        None => return false,
+    };
+    if !run.finish(language) {
+        //The worker panicked and took the interpreter with it, start again from a new one
+        language.set_input_source(Box::new(console_input.clone()));
+        language.log_info("The background run stopped unexpectedly, build the program again");
+    }
+    return true;
+}
+
+//Report a message in the console, through the background run while its worker holds the interpreter
+fn log_info(background: &mut Option<BackgroundRun>, language: &mut InterpreterObject, message: &str) {
+    match background {
+        Some(run) => run.log_info(message),
+        None => language.log_info(message),
+    }
+}
+
+//Whether a ribbon button does anything in the interpreter's state, buttons numbered from 0 on the left
+fn is_ribbon_button_enabled((state, running): (VmState, bool), button: usize) -> bool {
+    match button {
+        //Execute, from the start or from where the program stopped
+        3 => return state.is_executable() && !running,
+        //Step
+        4 => return !running && (state == VmState::Ready || state == VmState::Paused),
+        //Stop, or resume a paused program. A program waiting for input is running or paused
+        5 => return running || state == VmState::Paused || matches!(state, VmState::AwaitingInput { .. }),
+        //Reset
+        6 => return state != VmState::NotBuilt,
+        _ => return true,
+    }
+}
+
+//Draw what the interpreter is doing to the right of the ribbon buttons
+fn draw_ribbon_state<C: CharacterCache, G: Graphics<Texture = <C as CharacterCache>::Texture>>
+(c: &Context, g: &mut G, state: VmState, colors: &Palette, glyphs: &mut C){
+    let color = match state {
+        VmState::Faulted => colors.error_highlight,
+        VmState::AwaitingInput { .. } => colors.console_input,
+        _ => colors.text,
+    };
+    text::Text::new_color(color, 18).draw(&state.to_string(), glyphs, &c.draw_state,
+    c.transform.trans(528.0, 38.0), g).unwrap_or_default();
+}
+
 //Draw output background
 fn draw_output_console<G: Graphics>(c: &Context, g: &mut G, colors: &Palette, windowsize: &Size){
     Rectangle::new(colors.output_background).draw([0.0, windowsize.height - 200.0, windowsize.width, 200.0], &c.draw_state, c.transform, g);
 }
 
 //Draw the output buffer as text glyphs
+#[allow(clippy::too_many_arguments)]
 fn draw_output_buffer<C: CharacterCache, G: Graphics<Texture = <C as CharacterCache>::Texture>>
-(c: &Context, g: &mut G, language: &mut InterpreterObject, colors: &Palette, glyphs: &mut C, windowsize: &Size) {
-    let output_buffer = String::from(language.get_output());
-    let output_entry = String::from(language.get_console_entry());
-    let mut output_lines: Vec<String> = Vec::new();
-    output_lines.push(String::new());
-    for character in output_buffer.chars() {
-        if character != '\n'{
-            output_lines.last_mut().unwrap().push(character);
-        } else {
-            if output_lines.len() >= 11 {
-                output_lines.remove(0);
+(c: &Context, g: &mut G, segments: Vec<(Channel, &str)>, state: VmState, config: &VmConfig, console_entry: &String, colors: &Palette, glyphs: &mut C, windowsize: &Size) {
+    let output_entry = String::from(console_entry);
+    //Each line is a list of pieces, one for every channel the line has text from
+    let mut output_lines: Vec<Vec<(Channel, String)>> = Vec::new();
+    output_lines.push(Vec::new());
+    for (channel, segment) in segments {
+        for character in segment.chars() {
+            if character != '\n'{
+                let line = output_lines.last_mut().unwrap();
+                match line.last_mut() {
+                    Some((last, piece)) if *last == channel => piece.push(character),
+                    _ => line.push((channel, character.to_string())),
+                }
+            } else {
+                if output_lines.len() >= 11 {
+                    output_lines.remove(0);
+                }
+                output_lines.push(Vec::new());
             }
-            output_lines.push(String::new());
         }
     }
     for lines in 0..output_lines.len() {
-        text::Text::new_color(colors.text, 18).draw(&output_lines.get(lines).unwrap(), glyphs, &c.draw_state, 
-        c.transform.trans(10.0, (windowsize.height-180.0) + lines as f64 * 18.0), g).unwrap_or_default();
+        let mut x = 10.0;
+        for (channel, piece) in output_lines.get(lines).unwrap() {
+            let color = match channel {
+                Channel::Program => colors.text,
+                Channel::Input => colors.console_input,
+                Channel::Message => colors.console_message,
+            };
+            text::Text::new_color(color, 18).draw(piece, glyphs, &c.draw_state, 
+            c.transform.trans(x, (windowsize.height-180.0) + lines as f64 * 18.0), g).unwrap_or_default();
+            x += glyphs.width(18, piece).unwrap_or_default();
+        }
     }
-    if language.is_waiting() == true {
+    //The entry box is labelled with the kind of input the program is waiting for
+    if let VmState::AwaitingInput { kind } = state {
+        let prompt = match kind {
+            InputKind::Number => format!("?0 Number (0 - {}): ", config.max_cell_value),
+            InputKind::Text => String::from("?a Text: "),
+        };
         Rectangle::new(colors.border).draw([10.0, windowsize.height - 225.0, windowsize.width - 20.0, 25.0], &c.draw_state, c.transform, g);
-        text::Text::new_color(colors.text, 18).draw(&output_entry, glyphs, &c.draw_state, 
+        text::Text::new_color(colors.console_input, 18).draw(&prompt, glyphs, &c.draw_state, 
         c.transform.trans(10.0, windowsize.height-205.0), g).unwrap_or_default();
+        let prompt_width = glyphs.width(18, &prompt).unwrap_or_default();
+        text::Text::new_color(colors.text, 18).draw(&output_entry, glyphs, &c.draw_state, 
+        c.transform.trans(10.0 + prompt_width, windowsize.height-205.0), g).unwrap_or_default();
     }
 }
 
@@ -452,7 +756,7 @@ fn draw_help_window<G: Graphics>(c: &Context, g: &mut G, colors: &Palette, windo
 
 //Draw the help window text as glyphs
 fn draw_help_window_text<C: CharacterCache, G: Graphics<Texture = <C as CharacterCache>::Texture>>
-(c: &Context, g: &mut G, colors: &Palette, glyphs: &mut C, windowsize: &Size){
+(c: &Context, g: &mut G, language: &InterpreterObject, colors: &Palette, glyphs: &mut C, windowsize: &Size){
     Text::new_color(colors.text, 10).draw("'+' - Increment", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 80.0), g).unwrap_or_default();
     Text::new_color(colors.text, 10).draw("'-' - Decrement", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 90.0), g).unwrap_or_default();
     Text::new_color(colors.text, 10).draw("'<' - Shift index left", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 100.0), g).unwrap_or_default();
@@ -469,6 +773,133 @@ fn draw_help_window_text<C: CharacterCache, G: Graphics<Texture = <C as Characte
     Text::new_color(colors.text, 10).draw("'}' - Conditional jump marker", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 210.0), g).unwrap_or_default();
     Text::new_color(colors.text, 10).draw("':' - Non-conditional jump", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 220.0), g).unwrap_or_default();
     Text::new_color(colors.text, 10).draw("'=' - Non-conditional jump marker", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 230.0), g).unwrap_or_default();
+    //How '?a' lays text out in the array, for the mode in use
+    let string_input = match language.get_config().string_input {
+        StringInputMode::ZeroTerminated => ["'?a' text is zero-terminated:", "one character per cell, then a 0", "cell. The pointer is left on the 0"],
+        StringInputMode::LengthPrefixed => ["'?a' text is length-prefixed:", "the length, then one character per", "cell. The pointer is left past it"],
+        StringInputMode::Raw => ["'?a' text is raw:", "one character per cell, no end", "marker. The pointer is left past it"],
+    };
+    for line in 0..string_input.len() {
+        Text::new_color(colors.text, 10).draw(string_input[line], glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 250.0 + line as f64 * 10.0), g).unwrap_or_default();
+    }
+    Text::new_color(colors.text, 10).draw("Text that does not fit in the array", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 280.0), g).unwrap_or_default();
+    Text::new_color(colors.text, 10).draw("stops the program with an error", glyphs, &c.draw_state, c.transform.trans(windowsize.width-210.0, 290.0), g).unwrap_or_default();
+}
+
+//The first tape cell shown in the memory inspector and how many rows of cells fit.
+//Keeps the pointer in the middle of the visible window of the tape where possible
+fn get_inspector_tape_window(language: &InterpreterObject, windowsize: &Size) -> (usize, usize) {
+    let rows = ((windowsize.height - 210.0 - INSPECTOR_TOP) / INSPECTOR_ROW_HEIGHT).max(0.0) as usize;
+    let tape_len = language.get_tape().len();
+    let tape_rows = rows.saturating_sub(5) * 2 / 3;
+    let mut first_cell = language.get_pointer().saturating_sub(tape_rows / 2);
+    if first_cell + tape_rows > tape_len {
+        first_cell = tape_len.saturating_sub(tape_rows);
+    }
+    return (first_cell, tape_rows.min(tape_len));
+}
+
+//Tape cell listed in the memory inspector under a point on screen
+fn get_inspector_cell_at(point: &[f64; 2], language: &InterpreterObject, windowsize: &Size) -> Option<usize> {
+    if point[0] < windowsize.width - 215.0 || point[1] < INSPECTOR_TOP - 10.0 {
+        return None;
+    }
+    //The tape is listed below the two header rows
+    let row = ((point[1] - (INSPECTOR_TOP - 10.0)) / INSPECTOR_ROW_HEIGHT) as usize;
+    let (first_cell, tape_rows) = get_inspector_tape_window(language, windowsize);
+    if row >= 2 && row - 2 < tape_rows {
+        return Some(first_cell + row - 2);
+    }
+    return None;
+}
+
+//Stack header of the memory inspector under a point on screen, clicked to watch the stack depth
+fn is_inspector_stack_header_at(point: &[f64; 2], language: &InterpreterObject, windowsize: &Size) -> bool {
+    if point[0] < windowsize.width - 215.0 || point[1] < INSPECTOR_TOP - 10.0 {
+        return false;
+    }
+    //The stack header follows the two header rows, the tape and a blank row
+    let row = ((point[1] - (INSPECTOR_TOP - 10.0)) / INSPECTOR_ROW_HEIGHT) as usize;
+    let (_, tape_rows) = get_inspector_tape_window(language, windowsize);
+    return row == tape_rows + 3;
+}
+
+//The memory inspector prompt with what has been typed so far
+fn get_watch_prompt_text(prompt: WatchPrompt, entry: &str) -> String {
+    match prompt {
+        WatchPrompt::CellValue(cell) => return format!("Watch cell {} for value: {}_", cell, entry),
+        WatchPrompt::StackDepth => return format!("Watch stack depth: {}_", entry),
+    }
+}
+
+//Draw the memory inspector as glyphs: the cells around the pointer, then the stack from the top down.
+//The pointed cell is highlighted, recently written cells are marked with '*' and watched cells with '@',
+//followed by the values they are watched for. A prompt being typed is drawn under the header
+fn draw_inspector_text<C: CharacterCache, G: Graphics<Texture = <C as CharacterCache>::Texture>>
+(c: &Context, g: &mut G, language: &InterpreterObject, colors: &Palette, glyphs: &mut C, windowsize: &Size, prompt: Option<String>){
+    let left = windowsize.width - 210.0;
+    let row_height = INSPECTOR_ROW_HEIGHT;
+    let top = INSPECTOR_TOP;
+    let rows = ((windowsize.height - 210.0 - top) / row_height).max(0.0) as usize;
+    let tape = language.get_tape();
+    let stack = language.get_stack();
+    let pointer = language.get_pointer();
+    let step = language.get_step_count();
+    let mut row = 0;
+
+    let header = format!("Pointer: {}   Step: {}", pointer, step);
+    Text::new_color(colors.text, 10).draw(&header, glyphs, &c.draw_state, c.transform.trans(left, top), g).unwrap_or_default();
+    if let Some(prompt) = prompt {
+        Text::new_color(colors.console_input, 10).draw(&prompt, glyphs, &c.draw_state, c.transform.trans(left, top + row_height), g).unwrap_or_default();
+    }
+    row += 2;
+
+    let (first_cell, tape_rows) = get_inspector_tape_window(language, windowsize);
+    for cell in first_cell..(first_cell + tape_rows).min(tape.len()) {
+        let y = top + row as f64 * row_height;
+        let recent = match language.cell_written_at(cell) {
+            Some(written) => step - written < RECENT_CHANGE_STEPS,
+            None => false,
+        };
+        if cell == pointer {
+            Rectangle::new(colors.highlight).draw([left - 3.0, y - 10.0, 209.0, row_height], &c.draw_state, c.transform, g);
+        }
+        let marker = if recent { "*" } else { " " };
+        let watch_marker = if language.is_watched(cell) { "@" } else { " " };
+        let color = if recent { colors.recent_change } else { colors.text };
+        let mut line = format!("{}{}[{:>3}] {}", marker, watch_marker, cell, tape[cell]);
+        for watchpoint in language.get_watchpoints() {
+            if let Watchpoint::CellValue(watched, value) = watchpoint {
+                if *watched == cell {
+                    line.push_str(&format!("  @{}", value));
+                }
+            }
+        }
+        Text::new_color(color, 10).draw(&line, glyphs, &c.draw_state, c.transform.trans(left, y), g).unwrap_or_default();
+        row += 1;
+    }
+    row += 1;
+
+    let mut stack_header = format!("Stack (depth {})", stack.len());
+    for watchpoint in language.get_watchpoints() {
+        if let Watchpoint::StackDepth(depth) = watchpoint {
+            stack_header.push_str(&format!("  @{}", depth));
+        }
+    }
+    Text::new_color(colors.text, 10).draw(&stack_header, glyphs, &c.draw_state, c.transform.trans(left, top + row as f64 * row_height), g).unwrap_or_default();
+    row += 1;
+    let stack_rows = rows.saturating_sub(row + 1);
+    for (depth, value) in stack.iter().rev().enumerate() {
+        let y = top + row as f64 * row_height;
+        if depth >= stack_rows {
+            let more = format!("  ... {} more", stack.len() - depth);
+            Text::new_color(colors.text, 10).draw(&more, glyphs, &c.draw_state, c.transform.trans(left, y), g).unwrap_or_default();
+            break;
+        }
+        let line = format!("  {}", value);
+        Text::new_color(colors.text, 10).draw(&line, glyphs, &c.draw_state, c.transform.trans(left, y), g).unwrap_or_default();
+        row += 1;
+    }
 }
 
 //Draw the file chooser window and save the file from input
@@ -484,6 +915,34 @@ fn savefile(filename: &String, input: &Vec<String>) {
     file.write_all(output_buffer.as_bytes()).expect("Unable to write");
 }
 
+//Apply the settings in the VM settings file, or the defaults if there is no file.
+//Returns why the file could not be used, in which case the settings are left as they were
+fn load_vm_config(language: &mut InterpreterObject) -> Option<String> {
+    let text = match std::fs::read_to_string(VM_CONFIG_PATH) {
+        Ok(text) => text,
+        Err(_) => {
+            language.set_config(VmConfig::default());
+            return None;
+        }
+    };
+    match VmConfig::parse(&text) {
+        Ok(config) => {
+            language.set_config(config);
+            return None;
+        }
+        Err(message) => return Some(format!("Unable to use {}, {}", VM_CONFIG_PATH, message)),
+    }
+}
+
+//Trace file kept in the saves folder, named after the open file
+fn get_trace_path(filename: &String) -> String {
+    let stem = match filename.rsplit_once('.') {
+        Some((stem, _)) => stem,
+        None => filename.as_str(),
+    };
+    return format!("saves/{}.trace.jsonl", stem);
+}
+
 //Handle keyboard input for the input buffer
 fn handle_input(input_buffer: &mut Vec<String>, key: &Key, cursorpos: &mut [usize; 2], shift: &bool, control: &bool) {
     if *key == Key::Return {
@@ -623,11 +1082,88 @@ pub fn getupperchar(key: &i32) -> char{
     }
 }
 
+//The token to highlight in the editor: the token a runtime error happened on,
+//or the token about to execute while the program is built but not running
+fn get_token_highlight(language: &InterpreterObject, colors: &Palette) -> Option<(Span, [f32; 4])> {
+    if let Some(span) = language.get_fault_span() {
+        return Some((span, colors.error_highlight));
+    }
+    if language.get_state().is_executable() && !language.can_step() {
+        if let Some(span) = language.current_span() {
+            if span.end > span.start {
+                return Some((span, colors.highlight));
+            }
+        }
+    }
+    return None;
+}
+
+//First line of the input buffer drawn on screen, scrolled to keep the cursor visible
+fn get_draw_y_offset(cursorpos: &[usize; 2], windowsize: &Size) -> usize {
+    let font_size = 20.0;
+    if cursorpos[1] > ((windowsize.height / font_size) - 20.0) as usize {
+        return (cursorpos[1]) - ((windowsize.height / font_size) - 19.0) as usize;
+    }
+    return 0;
+}
+
+//Line of the input buffer under a point on screen, if the point is over the text area
+fn get_line_at(point: &[f64; 2], input: &Vec<String>, cursorpos: &[usize; 2], windowsize: &Size) -> Option<usize> {
+    if point[1] < 70.0 || point[1] > windowsize.height - 200.0 {
+        return None;
+    }
+    let line = get_draw_y_offset(cursorpos, windowsize) + ((point[1] - 70.0) / 25.0) as usize;
+    if line < input.len() {
+        return Some(line);
+    }
+    return None;
+}
+
+//Draw a marker in the gutter left of the text for every line with a breakpoint
+fn draw_breakpoint_gutter<G: Graphics>(c: &Context, g: &mut G, language: &InterpreterObject, input: &Vec<String>, colors: &Palette, 
+cursorpos: &[usize; 2], windowsize: &Size){
+    let draw_y_offset = get_draw_y_offset(cursorpos, windowsize);
+    for line in draw_y_offset..input.len() {
+        let y = 70.0 + ((line-draw_y_offset) as f64*25.0);
+        if y > windowsize.height - 200.0 {
+            break;
+        }
+        if language.has_breakpoint(line + 1) {
+            Ellipse::new(colors.breakpoint).draw([4.0, y + 7.0, 10.0, 10.0], &c.draw_state, c.transform, g);
+        }
+    }
+}
+
+//Tint every line behind the text by how many tokens on it have executed, the hottest line fully tinted
+fn draw_profile_heatmap<G: Graphics>(c: &Context, g: &mut G, language: &InterpreterObject, input: &Vec<String>, colors: &Palette, 
+cursorpos: &[usize; 2], windowsize: &Size){
+    let profile = language.get_profile();
+    let max_count = profile.get_max_line_count();
+    if max_count == 0 {
+        return;
+    }
+    let draw_y_offset = get_draw_y_offset(cursorpos, windowsize);
+    for line in draw_y_offset..input.len() {
+        let y = 70.0 + ((line-draw_y_offset) as f64*25.0);
+        if y > windowsize.height - 200.0 {
+            break;
+        }
+        let count = profile.get_line_count(line + 1);
+        if count > 0 {
+            let heat = count as f32 / max_count as f32;
+            let mut color = colors.heatmap;
+            color[3] *= 0.15 + 0.85 * heat;
+            Rectangle::new(color).draw([18.0, y, windowsize.width - 18.0, 25.0], &c.draw_state, c.transform, g);
+        }
+    }
+}
+
 //Draw the input buffer as text glyphs (Updated for performace)
+#[allow(clippy::too_many_arguments)]
 fn draw_input_buffer_updated<C: CharacterCache, G: Graphics<Texture = <C as CharacterCache>::Texture>>
-(c: &Context, g: &mut G, input: &Vec<String>, colors: &Palette, glyphs: &mut C, cursorpos: &[usize; 2], windowsize: &Size) {
+(c: &Context, g: &mut G, input: &Vec<String>, colors: &Palette, glyphs: &mut C, cursorpos: &[usize; 2], windowsize: &Size,
+token_highlight: &Option<(Span, [f32; 4])>) {
     let mut draw_x_offset = 0;
-    let mut draw_y_offset = 0;
     let font_size: u32 = 20;
     
     //Get the draw offset from the cursor position
@@ -635,9 +1171,7 @@ fn draw_input_buffer_updated<C: CharacterCache, G: Graphics<Texture = <C as Char
         if cursorpos[0] > (windowsize.width / font_size as f64) as usize {
             draw_x_offset = cursorpos[0] - (windowsize.width / font_size as f64) as usize;
         }
-        if cursorpos[1] > ((windowsize.height / font_size as f64) - 20.0) as usize {
-            draw_y_offset = (cursorpos[1]) - ((windowsize.height / font_size as f64) - 19.0) as usize;
-        }
+        let draw_y_offset = get_draw_y_offset(cursorpos, windowsize);
 
 
         //Draw lines as text
@@ -646,6 +1180,18 @@ fn draw_input_buffer_updated<C: CharacterCache, G: Graphics<Texture = <C as Char
             if input.get(line).unwrap().len() > draw_x_offset {
                 let draw_text = String::from(&input.get(line).unwrap()[draw_x_offset..input.get(line).unwrap().len()]);
                 let glyph_transform = c.transform.trans(20.0, 90.0 + ((line-draw_y_offset) as f64*25.0));
+                //Draw the token highlight behind the text
+                if let Some((span, color)) = token_highlight {
+                    if span.line == line + 1 && span.column > draw_x_offset {
+                        let line_text = input.get(line).unwrap();
+                        let before: String = line_text.chars().skip(draw_x_offset).take(span.column - 1 - draw_x_offset).collect();
+                        let token: String = line_text.chars().skip(span.column - 1).take(span.end_column - span.column).collect();
+                        let highlight_x = 20.0 + glyphs.width(font_size, &before).unwrap_or_default();
+                        let highlight_width = glyphs.width(font_size, &token).unwrap_or_default();
+                        Rectangle::new(*color).draw([highlight_x, 70.0 + ((line-draw_y_offset) as f64*25.0), highlight_width, 25.0], 
+                            &c.draw_state, c.transform, g);
+                    }
+                }
                 text::Text::new_color(colors.text, font_size).draw(&draw_text, glyphs, &c.draw_state, glyph_transform, g).unwrap_or_default();
                 if line == cursorpos[1] {
                     let cursorpostext = String::from(&input.get(line).unwrap()[draw_x_offset..cursorpos[0]]);